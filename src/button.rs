@@ -1,6 +1,7 @@
 use crate::rectangle::{RectPos, Rectangle};
-use crate::text::Text;
+use crate::text::{Text, TextStyle};
 use winit::dpi::PhysicalPosition;
+use winit::window::CursorIcon;
 
 pub struct ButtonConfig {
     pub rect_pos: RectPos,
@@ -11,6 +12,7 @@ pub struct ButtonConfig {
     pub text: &'static str,
     pub text_color: glyphon::Color,
     pub text_color_active: glyphon::Color,
+    pub text_style: TextStyle,
     pub on_click: Box<dyn Fn()>,
 }
 
@@ -18,6 +20,7 @@ pub struct Button {
     pub text: Text,
     pub rectangle: Rectangle,
     on_click: Box<dyn Fn()>,
+    pressed: bool,
 }
 
 impl Button {
@@ -36,8 +39,10 @@ impl Button {
                 cfg.text,
                 cfg.text_color,
                 cfg.text_color_active,
+                cfg.text_style,
             ),
             on_click: cfg.on_click,
+            pressed: false,
         }
     }
 
@@ -45,6 +50,27 @@ impl Button {
         (self.on_click)()
     }
 
+    pub fn cursor_icon(&self) -> CursorIcon {
+        CursorIcon::Pointer
+    }
+
+    pub fn press(&mut self) {
+        self.pressed = true;
+    }
+
+    /// Ends a press, returning whether it activates the button, which is the case if the pointer
+    /// is still over it.
+    pub fn release(&mut self, mouse_coords: PhysicalPosition<f64>) -> bool {
+        let activated = self.is_pressed(mouse_coords);
+        self.pressed = false;
+        activated
+    }
+
+    // a press is suspended while the pointer is outside the button, until it returns or is released
+    pub fn is_pressed(&self, mouse_coords: PhysicalPosition<f64>) -> bool {
+        self.pressed && self.is_hovered(mouse_coords)
+    }
+
     pub fn is_hovered(&self, mouse_coords: PhysicalPosition<f64>) -> bool {
         self.rectangle.is_hovered(mouse_coords)
     }
@@ -0,0 +1,54 @@
+pub trait Clipboard {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: &str);
+}
+
+pub struct SystemClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+impl SystemClipboard {
+    pub fn new() -> Option<Self> {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(Self { clipboard }),
+            Err(e) => {
+                log::warn!("system clipboard unavailable: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl Clipboard for SystemClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        if let Err(e) = self.clipboard.set_text(text) {
+            log::error!("could not write to clipboard: {e}");
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    content: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.content.clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.content = Some(text.to_owned());
+    }
+}
+
+pub fn new_clipboard() -> Box<dyn Clipboard> {
+    match SystemClipboard::new() {
+        Some(clipboard) => Box::new(clipboard),
+        None => Box::<MemoryClipboard>::default(),
+    }
+}
@@ -0,0 +1,229 @@
+use std::time::SystemTime;
+
+const MAX_HISTORY: usize = 100;
+const COALESCE_TIMEOUT_MS: u128 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    Typing,
+    Backspace,
+    Delete,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub at: usize,
+    pub deleted: String,
+    pub inserted: String,
+    pub cursor_before: usize,
+    pub selection_anchor_before: Option<usize>,
+    pub kind: EditKind,
+    pub time: SystemTime,
+}
+
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+}
+
+impl EditHistory {
+    pub fn push(&mut self, edit: Edit) {
+        self.redo_stack.clear();
+
+        if let Some(last) = self.undo_stack.last_mut() {
+            if last.coalesce(&edit) {
+                return;
+            }
+        }
+
+        if self.undo_stack.len() == MAX_HISTORY {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(edit);
+    }
+
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo_stack.pop()?;
+        self.redo_stack.push(edit.clone());
+        Some(edit)
+    }
+
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo_stack.pop()?;
+        self.undo_stack.push(edit.clone());
+        Some(edit)
+    }
+}
+
+impl Edit {
+    // merges consecutive typing or deleting into `self`, so they are undone in one step
+    fn coalesce(&mut self, next: &Edit) -> bool {
+        let in_time = next
+            .time
+            .duration_since(self.time)
+            .is_ok_and(|duration| duration.as_millis() < COALESCE_TIMEOUT_MS);
+        if !in_time || self.kind != next.kind {
+            return false;
+        }
+
+        let merged = match self.kind {
+            EditKind::Typing if self.at + self.inserted.len() == next.at => {
+                self.inserted.push_str(&next.inserted);
+                true
+            }
+            EditKind::Backspace if next.at + next.deleted.len() == self.at => {
+                self.deleted.insert_str(0, &next.deleted);
+                self.at = next.at;
+                true
+            }
+            EditKind::Delete if next.at == self.at => {
+                self.deleted.push_str(&next.deleted);
+                true
+            }
+            _ => false,
+        };
+
+        if merged {
+            self.time = next.time;
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn edit(kind: EditKind, at: usize, deleted: &str, inserted: &str, ms: u64) -> Edit {
+        Edit {
+            at,
+            deleted: deleted.to_owned(),
+            inserted: inserted.to_owned(),
+            cursor_before: at,
+            selection_anchor_before: None,
+            kind,
+            time: SystemTime::UNIX_EPOCH + Duration::from_millis(ms),
+        }
+    }
+
+    fn undo_steps(history: &mut EditHistory) -> usize {
+        std::iter::from_fn(|| history.undo()).count()
+    }
+
+    #[test]
+    fn typing_merges_into_one_step() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Typing, 0, "", "a", 0));
+        history.push(edit(EditKind::Typing, 1, "", "b", 100));
+        history.push(edit(EditKind::Typing, 2, "", "c", 200));
+
+        let undone = history.undo().unwrap();
+        assert_eq!((undone.at, undone.inserted.as_str()), (0, "abc"));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn typing_elsewhere_starts_a_new_step() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Typing, 0, "", "a", 0));
+        history.push(edit(EditKind::Typing, 5, "", "b", 100));
+        assert_eq!(undo_steps(&mut history), 2);
+    }
+
+    #[test]
+    fn backspace_merges_only_when_contiguous() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Backspace, 4, "d", "", 0));
+        history.push(edit(EditKind::Backspace, 3, "c", "", 100));
+        history.push(edit(EditKind::Backspace, 0, "a", "", 200));
+
+        let undone = history.undo().unwrap();
+        assert_eq!((undone.at, undone.deleted.as_str()), (0, "a"));
+        let undone = history.undo().unwrap();
+        assert_eq!((undone.at, undone.deleted.as_str()), (3, "cd"));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn delete_merges_only_at_the_same_position() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Delete, 2, "c", "", 0));
+        history.push(edit(EditKind::Delete, 2, "d", "", 100));
+        history.push(edit(EditKind::Delete, 1, "b", "", 200));
+
+        let undone = history.undo().unwrap();
+        assert_eq!((undone.at, undone.deleted.as_str()), (1, "b"));
+        let undone = history.undo().unwrap();
+        assert_eq!((undone.at, undone.deleted.as_str()), (2, "cd"));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn different_kinds_do_not_merge() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Typing, 0, "", "a", 0));
+        history.push(edit(EditKind::Backspace, 0, "a", "", 100));
+        history.push(edit(EditKind::Other, 0, "", "xyz", 200));
+        history.push(edit(EditKind::Other, 3, "", "xyz", 300));
+        assert_eq!(undo_steps(&mut history), 4);
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Typing, 0, "", "a", 0));
+        history.undo().unwrap();
+        history.push(edit(EditKind::Other, 0, "", "b", 5000));
+
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().unwrap().inserted, "b");
+    }
+
+    #[test]
+    fn redo_reapplies_undone_edit() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Typing, 0, "", "a", 0));
+        history.undo().unwrap();
+        assert_eq!(history.redo().unwrap().inserted, "a");
+        assert!(history.redo().is_none());
+        assert!(history.undo().is_some());
+    }
+
+    #[test]
+    fn edits_far_apart_in_time_do_not_merge() {
+        let mut history = EditHistory::default();
+        history.push(edit(EditKind::Typing, 0, "", "a", 0));
+        history.push(edit(EditKind::Typing, 1, "", "b", 999));
+        // the limit counts from the last merged edit
+        history.push(edit(EditKind::Typing, 2, "", "c", 1998));
+        history.push(edit(EditKind::Typing, 3, "", "d", 2998));
+
+        let undone = history.undo().unwrap();
+        assert_eq!(undone.inserted, "d");
+        assert_eq!(history.undo().unwrap().inserted, "abc");
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut history = EditHistory::default();
+        for i in 0..MAX_HISTORY + 10 {
+            history.push(edit(EditKind::Other, i, "", "x", i as u64));
+        }
+
+        assert_eq!(undo_steps(&mut history), MAX_HISTORY);
+    }
+
+    #[test]
+    fn capped_history_drops_oldest_edits() {
+        let mut history = EditHistory::default();
+        for i in 0..MAX_HISTORY + 1 {
+            history.push(edit(EditKind::Other, i, "", "x", i as u64));
+        }
+
+        let oldest = std::iter::from_fn(|| history.undo()).last().unwrap();
+        assert_eq!(oldest.at, 1);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::rectangle::{RectPos, Rectangle};
+use std::collections::HashMap;
+
+const FOCUS_RING_WIDTH: u32 = 3;
+const FOCUS_RING_COLOR: [f32; 3] = [0.2, 0.5, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusEvent {
+    Focus(usize),
+    Blur(usize),
+}
+
+#[derive(Debug, Default)]
+pub struct FocusManager {
+    focused: Option<usize>,
+    focus_visible: bool,
+    tab_indices: HashMap<usize, u32>,
+}
+
+impl FocusManager {
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+
+    /// Returns whether the widget has focus that was moved there with the keyboard, which is
+    /// when the focus ring is drawn.
+    pub fn is_focus_visible(&self, id: usize) -> bool {
+        self.focus_visible && self.focused == Some(id)
+    }
+
+    /// Widgets with a tab index come first in the tab order, the others follow in tree order.
+    pub fn set_tab_index(&mut self, id: usize, tab_index: u32) {
+        self.tab_indices.insert(id, tab_index);
+    }
+
+    pub fn set_focus(&mut self, id: Option<usize>, visible: bool) -> Vec<FocusEvent> {
+        self.focus_visible = visible && id.is_some();
+        if id == self.focused {
+            return Vec::new();
+        }
+
+        let events = self
+            .focused
+            .map(FocusEvent::Blur)
+            .into_iter()
+            .chain(id.map(FocusEvent::Focus))
+            .collect();
+        self.focused = id;
+        events
+    }
+
+    /// Moves focus to the next (or previous) of the focusable widgets, given in tree order.
+    pub fn focus_next(&mut self, focusable: &[usize], backwards: bool) -> Vec<FocusEvent> {
+        let mut tab_order = focusable.to_vec();
+        tab_order.sort_by_key(|id| match self.tab_indices.get(id) {
+            Some(tab_index) => (0, *tab_index),
+            None => (1, 0),
+        });
+        if tab_order.is_empty() {
+            return Vec::new();
+        }
+
+        let len = tab_order.len();
+        let next = match self
+            .focused
+            .and_then(|focused| tab_order.iter().position(|id| *id == focused))
+        {
+            Some(position) if backwards => (position + len - 1) % len,
+            Some(position) => (position + 1) % len,
+            None if backwards => len - 1,
+            None => 0,
+        };
+        self.set_focus(Some(tab_order[next]), true)
+    }
+}
+
+pub fn focus_ring(rect_pos: RectPos) -> Rectangle {
+    Rectangle::new(
+        RectPos {
+            top: rect_pos.top.saturating_sub(FOCUS_RING_WIDTH),
+            left: rect_pos.left.saturating_sub(FOCUS_RING_WIDTH),
+            bottom: rect_pos.bottom + FOCUS_RING_WIDTH,
+            right: rect_pos.right + FOCUS_RING_WIDTH,
+        },
+        FOCUS_RING_COLOR,
+        FOCUS_RING_COLOR,
+        FOCUS_RING_COLOR,
+        FOCUS_RING_COLOR,
+    )
+}
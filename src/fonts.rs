@@ -0,0 +1,99 @@
+use glyphon::{fontdb, FontSystem};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub const FALLBACK_FAMILY: &str = "Noto Sans";
+const FALLBACK_FONT: &[u8] = include_bytes!("../fonts/NotoSans-Regular.ttf");
+const FALLBACK_LOCALE: &str = "en-US";
+
+pub enum FontSource {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    NoFaces,
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(err) => write!(f, "can't read font file: {err}"),
+            FontError::NoFaces => write!(f, "no font faces found in font data"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// Creates a font system with the system fonts and the bundled fallback font. Without any system
+/// fonts, e.g. when running headless, only the bundled font is used.
+pub fn new_font_system() -> FontSystem {
+    let mut font_system = FontSystem::new();
+    if font_system.db().is_empty() {
+        return bundled_font_system();
+    }
+    load_font(&mut font_system, FontSource::Bytes(FALLBACK_FONT.to_vec()))
+        .expect("bundled font is valid");
+    font_system
+}
+
+/// Creates a font system that only knows the bundled fallback font, so text layout does not
+/// depend on the fonts installed on the machine.
+pub fn bundled_font_system() -> FontSystem {
+    let mut db = fontdb::Database::new();
+    let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(FALLBACK_FONT.to_vec())));
+
+    // cosmic-text only picks faces matching the exact weight and style, so the single bundled
+    // face is registered for all of them to keep bold and italic text from failing to shape
+    let faces: Vec<fontdb::FaceInfo> = ids.iter().filter_map(|id| db.face(*id)).cloned().collect();
+    for face in faces {
+        for weight in (100..=900).step_by(100) {
+            for style in [fontdb::Style::Normal, fontdb::Style::Italic] {
+                if face.weight.0 != weight || face.style != style {
+                    db.push_face_info(fontdb::FaceInfo {
+                        weight: fontdb::Weight(weight),
+                        style,
+                        ..face.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    db.set_serif_family(FALLBACK_FAMILY);
+    db.set_sans_serif_family(FALLBACK_FAMILY);
+    db.set_cursive_family(FALLBACK_FAMILY);
+    db.set_fantasy_family(FALLBACK_FAMILY);
+    db.set_monospace_family(FALLBACK_FAMILY);
+    FontSystem::new_with_locale_and_db(FALLBACK_LOCALE.to_string(), db)
+}
+
+/// Registers a TTF/OTF font (or collection) and returns the family names it can be referred to
+/// by in a `TextStyle`.
+pub fn load_font(
+    font_system: &mut FontSystem,
+    source: FontSource,
+) -> Result<Vec<String>, FontError> {
+    let data = match source {
+        FontSource::File(path) => std::fs::read(path).map_err(FontError::Io)?,
+        FontSource::Bytes(data) => data,
+    };
+
+    let db = font_system.db_mut();
+    let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(data)));
+    let mut families: Vec<String> = ids
+        .iter()
+        .filter_map(|id| db.face(*id))
+        .flat_map(|face| face.families.iter().map(|(family, _)| family.clone()))
+        .collect();
+    families.dedup();
+
+    if families.is_empty() {
+        return Err(FontError::NoFaces);
+    }
+    Ok(families)
+}
@@ -0,0 +1,36 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoverEvent {
+    Enter(usize),
+    Leave(usize),
+}
+
+/// Tracks the widget under the pointer, which is the topmost one where widgets overlap.
+#[derive(Debug, Default)]
+pub struct HoverTracker {
+    hovered: Option<usize>,
+}
+
+impl HoverTracker {
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    pub fn is_hovered(&self, id: usize) -> bool {
+        self.hovered == Some(id)
+    }
+
+    pub fn set_hovered(&mut self, id: Option<usize>) -> Vec<HoverEvent> {
+        if id == self.hovered {
+            return Vec::new();
+        }
+
+        let events = self
+            .hovered
+            .map(HoverEvent::Leave)
+            .into_iter()
+            .chain(id.map(HoverEvent::Enter))
+            .collect();
+        self.hovered = id;
+        events
+    }
+}
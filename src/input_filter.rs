@@ -0,0 +1,170 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+pub enum InputFilter {
+    None,
+    Digits,
+    CharClass(CharClass),
+    Custom(Box<dyn Fn(char) -> bool>),
+}
+
+/// A regex-like character class such as `[a-zA-Z_]`, `[^0-9]` or `\d`.
+#[derive(Debug, Clone)]
+pub struct CharClass {
+    negated: bool,
+    ranges: Vec<RangeInclusive<char>>,
+}
+
+impl InputFilter {
+    pub fn char_class(pattern: &str) -> Self {
+        Self::CharClass(CharClass::parse(pattern))
+    }
+
+    pub fn accepts(&self, c: char) -> bool {
+        match self {
+            InputFilter::None => true,
+            InputFilter::Digits => c.is_ascii_digit(),
+            InputFilter::CharClass(class) => class.matches(c),
+            InputFilter::Custom(predicate) => predicate(c),
+        }
+    }
+}
+
+impl fmt::Debug for InputFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputFilter::None => write!(f, "None"),
+            InputFilter::Digits => write!(f, "Digits"),
+            InputFilter::CharClass(class) => f.debug_tuple("CharClass").field(class).finish(),
+            InputFilter::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl CharClass {
+    pub fn parse(pattern: &str) -> Self {
+        let inner = pattern
+            .strip_prefix('[')
+            .and_then(|inner| inner.strip_suffix(']'))
+            .unwrap_or(pattern);
+        let (negated, inner) = match inner.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, inner),
+        };
+
+        let mut ranges = Vec::new();
+        let mut chars = inner.chars().peekable();
+        while let Some(c) = chars.next() {
+            let start = match c {
+                '\\' => match chars.next() {
+                    Some('d') => {
+                        ranges.push('0'..='9');
+                        continue;
+                    }
+                    Some('w') => {
+                        ranges.extend(['a'..='z', 'A'..='Z', '0'..='9', '_'..='_']);
+                        continue;
+                    }
+                    Some('s') => {
+                        ranges.extend([' '..=' ', '\t'..='\t', '\n'..='\n', '\r'..='\r']);
+                        continue;
+                    }
+                    Some(escaped) => escaped,
+                    None => '\\',
+                },
+                c => c,
+            };
+
+            // a `-` only forms a range if there is a character after it, and reversed ranges such
+            // as `z-a` are taken as if written the right way round
+            let mut lookahead = chars.clone();
+            if let (Some('-'), Some(end)) = (lookahead.next(), lookahead.next()) {
+                chars = lookahead;
+                ranges.push(start.min(end)..=start.max(end));
+            } else {
+                ranges.push(start..=start);
+            }
+        }
+
+        Self { negated, ranges }
+    }
+
+    pub fn matches(&self, c: char) -> bool {
+        self.ranges.iter().any(|range| range.contains(&c)) != self.negated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(pattern: &str, candidates: &str) -> String {
+        let class = CharClass::parse(pattern);
+        candidates.chars().filter(|c| class.matches(*c)).collect()
+    }
+
+    #[test]
+    fn single_characters_and_ranges() {
+        assert_eq!(accepted("[abc]", "abcd"), "abc");
+        assert_eq!(accepted("[a-cx]", "abcdxy"), "abcx");
+        assert_eq!(accepted("[a-zA-Z_]", "aZ_9-"), "aZ_");
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(accepted("[^0-9]", "a1b2"), "ab");
+        // `^` is only special at the start
+        assert_eq!(accepted("[a^]", "a^b"), "a^");
+    }
+
+    #[test]
+    fn shorthand_classes() {
+        assert_eq!(accepted("\\d", "a1 _"), "1");
+        assert_eq!(accepted("[\\w]", "a1 _-"), "a1_");
+        assert_eq!(accepted("[\\s]", "a \t\n\r"), " \t\n\r");
+        assert_eq!(accepted("[^\\d]", "a1"), "a");
+        assert_eq!(accepted("[\\d.]", "1.a"), "1.");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(accepted("[\\-a]", "-ab"), "-a");
+        assert_eq!(accepted("[\\]]", "]a"), "]");
+        assert_eq!(accepted("[\\\\]", "\\a"), "\\");
+        assert_eq!(accepted("[\\^]", "^a"), "^");
+        // a trailing backslash matches itself
+        assert_eq!(accepted("[a\\]", "a\\b"), "a\\");
+    }
+
+    #[test]
+    fn dash_without_range_end_is_literal() {
+        assert_eq!(accepted("[a-]", "a-b"), "a-");
+        assert_eq!(accepted("[-a]", "a-b"), "a-");
+        assert_eq!(accepted("[0-9-]", "5-x"), "5-");
+    }
+
+    #[test]
+    fn reversed_ranges_are_swapped() {
+        assert_eq!(accepted("[z-a]", "amz0"), "amz");
+        assert_eq!(accepted("[9-0]", "05a"), "05");
+    }
+
+    #[test]
+    fn brackets_are_optional() {
+        assert_eq!(accepted("a-c", "abd"), "ab");
+    }
+
+    #[test]
+    fn empty_class_matches_nothing() {
+        assert_eq!(accepted("[]", "a1 "), "");
+        assert_eq!(accepted("[^]", "a1 "), "a1 ");
+    }
+
+    #[test]
+    fn input_filter_uses_char_class() {
+        let filter = InputFilter::char_class("[!-~]");
+        assert!(filter.accepts('a'));
+        assert!(!filter.accepts(' '));
+        assert!(!filter.accepts('é'));
+    }
+}
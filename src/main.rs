@@ -1,24 +1,41 @@
-use glyphon::{Color, FontSystem, Resolution, SwashCache, TextArea, TextAtlas, TextRenderer};
+use glyphon::{
+    Color, Family, FontSystem, Resolution, SwashCache, TextArea, TextAtlas, TextRenderer, Weight,
+};
+use input_filter::InputFilter;
 use rectangle::*;
-use std::time::SystemTime;
+use scroll::Scrollable;
+use std::time::{Instant, SystemTime};
+use validation::{CrossFieldValidator, Form, FormValues, Rule, Validator};
 use wgpu::util::DeviceExt;
 use winit::{
-    dpi::PhysicalPosition,
+    dpi::{PhysicalPosition, PhysicalSize},
     event::{
         ElementState,
         Event::{self, UserEvent},
-        WindowEvent,
+        Ime, MouseScrollDelta, WindowEvent,
     },
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget},
-    keyboard::{Key, NamedKey},
+    keyboard::{Key, ModifiersState, NamedKey},
     platform::modifier_supplement::KeyEventExtModifierSupplement,
-    window::{Window, WindowBuilder},
+    window::{CursorIcon, ImePurpose, Window, WindowBuilder},
 };
 
 mod button;
+mod clipboard;
+mod edit_history;
+mod focus;
+mod fonts;
+mod hover;
+mod input_filter;
+mod pointer;
 mod rectangle;
+mod scroll;
+mod scroll_view;
 mod text;
+mod text_area;
 mod text_field;
+mod text_measure;
+mod validation;
 
 #[repr(C)]
 #[derive(Clone, Debug, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -34,9 +51,60 @@ pub struct Id(usize);
 pub enum Component {
     Button(Id, button::Button),
     TextField(Id, text_field::TextField),
+    TextArea(Id, text_area::TextArea),
+    ScrollView(Id, scroll_view::ScrollView),
     Text(Id, text::Text),
 }
 
+impl Component {
+    fn id(&self) -> usize {
+        match self {
+            Component::Button(Id(id), _)
+            | Component::TextField(Id(id), _)
+            | Component::TextArea(Id(id), _)
+            | Component::ScrollView(Id(id), _)
+            | Component::Text(Id(id), _) => *id,
+        }
+    }
+
+    fn rect_pos(&self) -> RectPos {
+        match self {
+            Component::Button(_id, button) => button.rectangle.position,
+            Component::TextField(_id, text_field)
+            | Component::TextArea(_id, text_area::TextArea { text_field }) => {
+                text_field.rectangle.position
+            }
+            Component::ScrollView(_id, scroll_view) => scroll_view.rectangle.position,
+            Component::Text(_id, text) => text.rect_pos(),
+        }
+    }
+
+    fn is_hovered(&self, position: PhysicalPosition<f64>) -> bool {
+        self.rect_pos().contains(position)
+    }
+
+    fn is_focusable(&self) -> bool {
+        !matches!(self, Component::Text(..))
+    }
+
+    // text areas are edited through the same text field as single-line fields
+    fn text_field(&self) -> Option<&text_field::TextField> {
+        match self {
+            Component::TextField(_id, text_field)
+            | Component::TextArea(_id, text_area::TextArea { text_field }) => Some(text_field),
+            _ => None,
+        }
+    }
+
+    fn text_field_mut(&mut self) -> Option<&mut text_field::TextField> {
+        match self {
+            Component::TextField(_id, text_field)
+            | Component::TextArea(_id, text_area::TextArea { text_field }) => Some(text_field),
+            _ => None,
+        }
+    }
+}
+
 impl Vertex {
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -71,6 +139,59 @@ impl Vertex {
 struct InputState {
     clicked: bool,
     mouse_coords: PhysicalPosition<f64>,
+    modifiers: ModifiersState,
+    last_click: Option<SystemTime>,
+    click_count: u32,
+    ime_allowed: bool,
+    cursor_icon: CursorIcon,
+}
+
+const MULTI_CLICK_TIMEOUT_MS: u128 = 400;
+const BRAND_FONT_FILE: &str = "FiraMono-Medium.ttf";
+const BRAND_FONT: &[u8] = include_bytes!("../fonts/FiraMono-Medium.ttf");
+
+impl InputState {
+    fn shortcut_key(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.modifiers.super_key()
+        } else {
+            self.modifiers.control_key()
+        }
+    }
+
+    fn word_key(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.modifiers.alt_key()
+        } else {
+            self.modifiers.control_key()
+        }
+    }
+
+    fn register_click(&mut self) {
+        let now = SystemTime::now();
+        let is_multi_click = self.last_click.is_some_and(|last_click| {
+            now.duration_since(last_click)
+                .is_ok_and(|duration| duration.as_millis() < MULTI_CLICK_TIMEOUT_MS)
+        });
+        self.click_count = if is_multi_click {
+            self.click_count + 1
+        } else {
+            1
+        };
+        self.last_click = Some(now);
+    }
+}
+
+// a brand font in a `fonts` directory next to the executable replaces the embedded copy
+fn brand_font() -> fonts::FontSource {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("fonts").join(BRAND_FONT_FILE)))
+        .filter(|path| path.is_file())
+        .map_or_else(
+            || fonts::FontSource::Bytes(BRAND_FONT.to_vec()),
+            fonts::FontSource::File,
+        )
 }
 
 enum GUIEvent {
@@ -89,7 +210,14 @@ struct State<'window> {
     text_atlas: TextAtlas,
     text_cache: SwashCache,
     font_system: FontSystem,
+    text_measurer: text_measure::TextMeasurer,
+    clipboard: Box<dyn clipboard::Clipboard>,
     components: Vec<Component>,
+    forms: Vec<Form>,
+    focus: focus::FocusManager,
+    hover: hover::HoverTracker,
+    pointer: pointer::PointerCapture,
+    kinetic_scroll: scroll::KineticScroll,
     input_state: InputState,
 }
 
@@ -100,6 +228,11 @@ impl<'window> State<'window> {
         let input_state = InputState {
             clicked: false,
             mouse_coords,
+            modifiers: ModifiersState::empty(),
+            last_click: None,
+            click_count: 0,
+            ime_allowed: false,
+            cursor_icon: CursorIcon::Default,
         };
 
         let instance = wgpu::Instance::default();
@@ -137,7 +270,10 @@ impl<'window> State<'window> {
 
         surface.configure(&device, &config);
 
-        let mut font_system = FontSystem::new();
+        let mut font_system = fonts::new_font_system();
+        if let Err(err) = fonts::load_font(&mut font_system, brand_font()) {
+            log::warn!("can't load brand font: {err}");
+        }
         let text_cache = SwashCache::new();
         let mut text_atlas = TextAtlas::new(&device, &queue, swapchain_format);
         let text_renderer = TextRenderer::new(
@@ -198,9 +334,9 @@ impl<'window> State<'window> {
         let button = button::Button::new(
             button::ButtonConfig {
                 rect_pos: RectPos {
-                    top: 125,
+                    top: 170,
                     left: 100,
-                    bottom: 225,
+                    bottom: 270,
                     right: 400,
                 },
                 fill_color: [0.5, 0.0, 0.5],
@@ -210,6 +346,14 @@ impl<'window> State<'window> {
                 text: "Submit 🚀",
                 text_color: Color::rgb(200, 200, 200),
                 text_color_active: Color::rgb(255, 255, 255),
+                text_style: text::TextStyle {
+                    weight: Weight::BOLD,
+                    align: text::TextAlign::Center,
+                    overflow: text::TextOverflow::ShrinkToFit {
+                        min_font_size: 16.0,
+                    },
+                    ..Default::default()
+                },
                 on_click: Box::new(move || {
                     let _ = events_proxy_clone.send_event(GUIEvent::SuccessEvent(Id(1)));
                 }),
@@ -230,6 +374,160 @@ impl<'window> State<'window> {
                 border_color: [0.3, 0.3, 0.3],
                 border_color_active: [0.1, 0.1, 0.1],
                 text_color: Color::rgb(10, 10, 10),
+                text_style: text::TextStyle::default(),
+                password: false,
+                password_toggle: false,
+                placeholder: Some("Name"),
+                max_length: Some(20),
+                input_filter: InputFilter::Custom(Box::new(|c| {
+                    c.is_alphabetic() || c == ' ' || c == '-'
+                })),
+                validators: vec![
+                    Validator {
+                        rule: Rule::Required,
+                        message: "Please enter your name",
+                    },
+                    Validator {
+                        rule: Rule::MinLength(2),
+                        message: "Name is too short",
+                    },
+                ],
+            },
+            &mut font_system,
+        );
+
+        let text_area = text_area::TextArea::new(
+            text_area::TextAreaConfig {
+                rect_pos: RectPos {
+                    top: 50,
+                    left: 450,
+                    bottom: 380,
+                    right: 750,
+                },
+                fill_color: [0.9, 0.9, 0.9],
+                fill_color_active: [1.0, 1.0, 1.0],
+                border_color: [0.3, 0.3, 0.3],
+                border_color_active: [0.1, 0.1, 0.1],
+                text_color: Color::rgb(10, 10, 10),
+                text_style: text::TextStyle {
+                    font_size: 20.0,
+                    line_height: 28.0,
+                    align: text::TextAlign::Justify,
+                    vertical_align: text::VerticalAlign::Top,
+                    ..Default::default()
+                },
+                placeholder: Some("Notes"),
+                max_length: None,
+                input_filter: InputFilter::None,
+                validators: vec![Validator {
+                    rule: Rule::MaxLength(500),
+                    message: "Notes are too long",
+                }],
+            },
+            &mut font_system,
+        );
+
+        let password_field = text_field::TextField::new(
+            text_field::TextFieldConfig {
+                rect_pos: RectPos {
+                    top: 450,
+                    left: 100,
+                    bottom: 520,
+                    right: 400,
+                },
+                fill_color: [0.9, 0.9, 0.9],
+                fill_color_active: [1.0, 1.0, 1.0],
+                border_color: [0.3, 0.3, 0.3],
+                border_color_active: [0.1, 0.1, 0.1],
+                text_color: Color::rgb(10, 10, 10),
+                text_style: text::TextStyle {
+                    overflow: text::TextOverflow::Ellipsis,
+                    ..Default::default()
+                },
+                password: true,
+                password_toggle: true,
+                placeholder: Some("Password (at least 8 characters)"),
+                max_length: Some(32),
+                // printable ASCII without spaces
+                input_filter: InputFilter::char_class("[!-~]"),
+                validators: vec![
+                    Validator {
+                        rule: Rule::Required,
+                        message: "Please enter a password",
+                    },
+                    Validator {
+                        rule: Rule::MinLength(8),
+                        message: "Use at least 8 characters",
+                    },
+                ],
+            },
+            &mut font_system,
+        );
+
+        let age_field = text_field::TextField::new(
+            text_field::TextFieldConfig {
+                rect_pos: RectPos {
+                    top: 450,
+                    left: 450,
+                    bottom: 520,
+                    right: 750,
+                },
+                fill_color: [0.9, 0.9, 0.9],
+                fill_color_active: [1.0, 1.0, 1.0],
+                border_color: [0.3, 0.3, 0.3],
+                border_color_active: [0.1, 0.1, 0.1],
+                text_color: Color::rgb(10, 10, 10),
+                text_style: text::TextStyle {
+                    family: Family::Name("Fira Mono"),
+                    // faces only match their exact weight, and the bundled face is a medium one
+                    weight: Weight::MEDIUM,
+                    align: text::TextAlign::End,
+                    ..Default::default()
+                },
+                password: false,
+                password_toggle: false,
+                placeholder: Some("Age"),
+                max_length: Some(3),
+                input_filter: InputFilter::Digits,
+                validators: vec![
+                    Validator {
+                        rule: Rule::Format(InputFilter::Digits),
+                        message: "Age must be a number",
+                    },
+                    Validator {
+                        rule: Rule::Custom(Box::new(|age| {
+                            age.parse::<u32>().is_ok_and(|age| age >= 18)
+                        })),
+                        message: "You must be 18 or older",
+                    },
+                ],
+            },
+            &mut font_system,
+        );
+
+        let licenses = scroll_view::ScrollView::new(
+            scroll_view::ScrollViewConfig {
+                rect_pos: RectPos {
+                    top: 50,
+                    left: 800,
+                    bottom: 520,
+                    right: 1100,
+                },
+                fill_color: [0.95, 0.95, 0.95],
+                border_color: [0.3, 0.3, 0.3],
+                content: concat!(
+                    include_str!("../fonts/NotoSans-LICENSE"),
+                    "\n",
+                    include_str!("../fonts/FiraMono-LICENSE"),
+                )
+                .to_string(),
+                text_color: Color::rgb(10, 10, 10),
+                text_style: text::TextStyle {
+                    font_size: 16.0,
+                    line_height: 22.0,
+                    vertical_align: text::VerticalAlign::Top,
+                    ..Default::default()
+                },
             },
             &mut font_system,
         );
@@ -237,8 +535,33 @@ impl<'window> State<'window> {
         let components = vec![
             Component::Button(Id(0), button),
             Component::TextField(Id(1), text_field),
+            Component::TextArea(Id(3), text_area),
+            Component::TextField(Id(4), password_field),
+            Component::TextField(Id(5), age_field),
+            Component::ScrollView(Id(6), licenses),
         ];
 
+        // name, password and age first, then the notes and the submit button
+        let mut focus = focus::FocusManager::default();
+        [1, 4, 5, 3, 0, 6]
+            .into_iter()
+            .enumerate()
+            .for_each(|(tab_index, id)| focus.set_tab_index(id, tab_index as u32));
+
+        let forms = vec![Form {
+            fields: vec![1, 3, 4, 5],
+            submit_button: 0,
+            submit_on_enter: true,
+            cross_field_validators: vec![CrossFieldValidator {
+                field: 4,
+                message: "Password must not contain your name",
+                check: Box::new(|values| {
+                    let name = values.get(1).trim().to_lowercase();
+                    name.is_empty() || !values.get(4).to_lowercase().contains(&name)
+                }),
+            }],
+        }];
+
         Self {
             window,
             surface,
@@ -251,7 +574,14 @@ impl<'window> State<'window> {
             text_cache,
             text_renderer,
             font_system,
+            text_measurer: text_measure::TextMeasurer::default(),
+            clipboard: clipboard::new_clipboard(),
             components,
+            forms,
+            focus,
+            hover: hover::HoverTracker::default(),
+            pointer: pointer::PointerCapture::default(),
+            kinetic_scroll: scroll::KineticScroll::default(),
             input_state,
         }
     }
@@ -270,38 +600,399 @@ impl<'window> State<'window> {
     }
 
     fn handle_click(&mut self) {
+        let mouse_coords = self.input_state.mouse_coords;
+        // plain text under the pointer takes the click, like it takes the hover
+        let hovered = self
+            .component_at(mouse_coords)
+            .filter(|component| component.is_focusable())
+            .map(Component::id);
+        self.set_focus(hovered, false);
+        self.kinetic_scroll.stop();
+        if let Some(id) = hovered {
+            self.pointer.capture(id, mouse_coords);
+        }
+
         self.components
             .iter_mut()
             .for_each(|component| match component {
-                Component::Button(_id, button) => {
-                    if button.rectangle.is_hovered(self.input_state.mouse_coords) {
-                        button.click();
+                Component::Button(Id(id), button) if hovered == Some(*id) => button.press(),
+                Component::ScrollView(Id(id), scroll_view) if hovered == Some(*id) => {
+                    scroll_view.press(mouse_coords)
+                }
+                component if hovered == Some(component.id()) => {
+                    let Some(text_field) = component.text_field_mut() else {
+                        return;
+                    };
+                    text_field.cancel_preedit(&mut self.font_system);
+                    let toggle_hovered = text_field
+                        .password_toggle
+                        .as_ref()
+                        .is_some_and(|toggle| toggle.is_hovered(mouse_coords));
+                    match self.input_state.click_count {
+                        _ if toggle_hovered => {
+                            text_field.toggle_password_visibility(&mut self.font_system)
+                        }
+                        1 => text_field
+                            .place_cursor(mouse_coords, self.input_state.modifiers.shift_key()),
+                        2 => text_field.select_word(mouse_coords),
+                        _ => text_field.select_all(),
                     }
                 }
-                Component::TextField(_id, text_field) => {
-                    if text_field
-                        .rectangle
-                        .is_hovered(self.input_state.mouse_coords)
-                    {
-                        text_field.set_active();
-                    } else {
-                        text_field.set_inactive();
+                _ => (),
+            });
+
+        self.validate_forms(None);
+        self.update_ime();
+        self.update_cursor_icon();
+    }
+
+    fn handle_drag(&mut self, event: pointer::DragEvent) {
+        let selecting = event.phase != pointer::DragPhase::End && self.input_state.click_count == 1;
+        self.components
+            .iter_mut()
+            .filter(|component| component.id() == event.id)
+            .for_each(|component| match component {
+                Component::ScrollView(_id, scroll_view) => scroll_view.drag(event.delta),
+                component => {
+                    if let Some(text_field) = component.text_field_mut().filter(|_| selecting) {
+                        text_field.place_cursor(event.position, true);
                     }
                 }
+            });
+    }
+
+    /// Scrolls the innermost scrollable widget under `position`, passing on what it can't scroll
+    /// to the next one containing it. Returns whether anything scrolled.
+    fn scroll_at(&mut self, position: PhysicalPosition<f64>, dx: f32, dy: f32) -> bool {
+        let mut scrollable: Vec<(RectPos, &mut dyn Scrollable)> = self
+            .components
+            .iter_mut()
+            .filter(|component| component.is_hovered(position))
+            .filter_map(|component| match component {
+                Component::ScrollView(_id, scroll_view) => Some((
+                    scroll_view.rectangle.position,
+                    scroll_view as &mut dyn Scrollable,
+                )),
+                component => component.text_field_mut().map(|text_field| {
+                    (
+                        text_field.rectangle.position,
+                        text_field as &mut dyn Scrollable,
+                    )
+                }),
+            })
+            .collect();
+        scrollable.sort_by_key(|(rect_pos, _)| {
+            (rect_pos.right - rect_pos.left) * (rect_pos.bottom - rect_pos.top)
+        });
+
+        let mut remaining = (dx, dy);
+        let mut scrolled = false;
+        for (_, scrollable) in scrollable {
+            let (scrolled_x, scrolled_y) = scrollable.scroll_by(remaining.0, remaining.1);
+            remaining = (remaining.0 - scrolled_x, remaining.1 - scrolled_y);
+            scrolled |= scrolled_x != 0.0 || scrolled_y != 0.0;
+        }
+
+        if scrolled {
+            self.update_ime();
+        }
+        scrolled
+    }
+
+    fn update_scroll(&mut self) {
+        if let Some((position, (dx, dy))) = self.kinetic_scroll.step(Instant::now()) {
+            if (dx, dy) != (0.0, 0.0) && !self.scroll_at(position, dx, dy) {
+                self.kinetic_scroll.stop();
+            }
+        }
+    }
+
+    fn handle_release(&mut self) {
+        if let Some(event) = self.pointer.release(self.input_state.mouse_coords) {
+            self.handle_drag(event);
+        }
+
+        let mut released_button = None;
+        self.components
+            .iter_mut()
+            .for_each(|component| match component {
+                Component::Button(Id(id), button) => {
+                    let activated = button.release(self.input_state.mouse_coords);
+                    if activated {
+                        released_button = Some(*id);
+                    }
+                }
+                Component::ScrollView(_id, scroll_view) => scroll_view.release(),
                 _ => (),
             });
+        self.update_cursor_icon();
+
+        if let Some(id) = released_button {
+            self.activate_button(id);
+            self.update_ime();
+        }
+    }
+
+    fn activate_button(&mut self, button_id: usize) {
+        // a form's submit button only fires if all of its fields are valid
+        if self.validate_forms(Some(button_id)) {
+            self.components
+                .iter_mut()
+                .for_each(|component| match component {
+                    Component::Button(Id(id), button) if *id == button_id => button.click(),
+                    _ => (),
+                });
+        }
+    }
+
+    // the button pressed by Enter in the focused field, if it is a single-line field of a form
+    fn default_button(&self, field_id: usize) -> Option<usize> {
+        let is_text_field = self.components.iter().any(
+            |component| matches!(component, Component::TextField(Id(id), _) if *id == field_id),
+        );
+        self.forms
+            .iter()
+            .find(|form| is_text_field && form.submit_on_enter && form.fields.contains(&field_id))
+            .map(|form| form.submit_button)
+    }
+
+    fn is_button(&self, button_id: usize) -> bool {
+        self.components
+            .iter()
+            .any(|component| matches!(component, Component::Button(Id(id), _) if *id == button_id))
+    }
+
+    fn set_focus(&mut self, id: Option<usize>, visible: bool) {
+        let events = self.focus.set_focus(id, visible);
+        self.handle_focus_events(events);
+    }
+
+    fn focus_next(&mut self, backwards: bool) {
+        let focusable: Vec<usize> = self
+            .components
+            .iter()
+            .filter(|component| component.is_focusable())
+            .map(Component::id)
+            .collect();
+        let events = self.focus.focus_next(&focusable, backwards);
+        self.handle_focus_events(events);
+    }
+
+    // the topmost widget under the pointer, later components are drawn on top
+    fn component_at(&self, position: PhysicalPosition<f64>) -> Option<&Component> {
+        self.components
+            .iter()
+            .rev()
+            .find(|component| component.is_hovered(position))
+    }
+
+    fn widget_at(&self, position: PhysicalPosition<f64>) -> Option<usize> {
+        self.component_at(position).map(Component::id)
+    }
+
+    fn set_hovered(&mut self, id: Option<usize>) {
+        for event in self.hover.set_hovered(id) {
+            self.components
+                .iter_mut()
+                .for_each(|component| match (component, event) {
+                    (
+                        Component::ScrollView(Id(id), scroll_view),
+                        hover::HoverEvent::Enter(target),
+                    ) if *id == target => scroll_view.set_hovered(true),
+                    (
+                        Component::ScrollView(Id(id), scroll_view),
+                        hover::HoverEvent::Leave(target),
+                    ) if *id == target => scroll_view.set_hovered(false),
+                    _ => (),
+                });
+        }
+        self.update_cursor_icon();
+    }
+
+    // a widget capturing the pointer keeps its cursor while dragged outside of it
+    fn update_cursor_icon(&mut self) {
+        let target = self.pointer.captured().or(self.hover.hovered());
+        let mouse_coords = self.input_state.mouse_coords;
+        let cursor_icon = self
+            .components
+            .iter()
+            .find_map(|component| match component {
+                Component::Button(Id(id), button) if target == Some(*id) => {
+                    Some(button.cursor_icon())
+                }
+                Component::ScrollView(Id(id), scroll_view) if target == Some(*id) => {
+                    Some(scroll_view.cursor_icon())
+                }
+                component if target == Some(component.id()) => component
+                    .text_field()
+                    .map(|text_field| text_field.cursor_icon(mouse_coords)),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        if self.input_state.cursor_icon != cursor_icon {
+            self.input_state.cursor_icon = cursor_icon;
+            self.window.set_cursor_icon(cursor_icon);
+        }
+    }
+
+    fn handle_focus_events(&mut self, events: Vec<focus::FocusEvent>) {
+        for event in events {
+            let target = match event {
+                focus::FocusEvent::Blur(target) | focus::FocusEvent::Focus(target) => target,
+            };
+            let Some(text_field) = self
+                .components
+                .iter_mut()
+                .find(|component| component.id() == target)
+                .and_then(Component::text_field_mut)
+            else {
+                continue;
+            };
+            match event {
+                focus::FocusEvent::Blur(_) => {
+                    text_field.cancel_preedit(&mut self.font_system);
+                    text_field.set_inactive();
+                }
+                focus::FocusEvent::Focus(_) => text_field.set_active(),
+            }
+        }
+    }
+
+    /// Validates all forms, showing errors on fields the user already left. Submitting a form
+    /// through `submit_button` shows the errors of all its fields and returns whether it is valid.
+    fn validate_forms(&mut self, submit_button: Option<usize>) -> bool {
+        let mut valid = true;
+        for form in self.forms.iter() {
+            let submitted = submit_button == Some(form.submit_button);
+            let errors: Vec<(usize, Option<&'static str>)> = {
+                let fields: Vec<(usize, &text_field::TextField)> = self
+                    .components
+                    .iter()
+                    .filter(|component| form.fields.contains(&component.id()))
+                    .filter_map(|component| Some((component.id(), component.text_field()?)))
+                    .collect();
+                let values = FormValues::new(
+                    fields
+                        .iter()
+                        .map(|(id, text_field)| (*id, text_field.content.as_str()))
+                        .collect(),
+                );
+                fields
+                    .iter()
+                    .map(|(id, text_field)| {
+                        let error = text_field
+                            .validation_error()
+                            .or_else(|| form.cross_field_error(*id, &values));
+                        (*id, error)
+                    })
+                    .collect()
+            };
+
+            for component in self.components.iter_mut() {
+                let id = component.id();
+                let Some(text_field) = component.text_field_mut() else {
+                    continue;
+                };
+                if let Some((_, error)) = errors.iter().find(|(field, _)| *field == id) {
+                    if submitted {
+                        text_field.touched = true;
+                        valid &= error.is_none();
+                    }
+                    if text_field.touched {
+                        text_field.set_error(&mut self.font_system, *error);
+                    }
+                }
+            }
+        }
+        valid
+    }
+
+    fn update_ime(&mut self) {
+        let active_text_field = self
+            .components
+            .iter()
+            .filter_map(Component::text_field)
+            .find(|text_field| text_field.active);
+
+        if self.input_state.ime_allowed != active_text_field.is_some() {
+            self.input_state.ime_allowed = active_text_field.is_some();
+            self.window.set_ime_allowed(self.input_state.ime_allowed);
+        }
+
+        if let Some(text_field) = active_text_field {
+            self.window.set_ime_purpose(if text_field.password {
+                ImePurpose::Password
+            } else {
+                ImePurpose::Normal
+            });
+
+            let RectPos {
+                top,
+                left,
+                bottom,
+                right,
+            } = text_field.get_cursor().position;
+            self.window.set_ime_cursor_area(
+                PhysicalPosition::new(left, top),
+                PhysicalSize::new(right - left, bottom - top),
+            );
+        }
     }
 
     fn input(&mut self, event: &WindowEvent, elwt: &EventLoopWindowTarget<GUIEvent>) -> bool {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
                 self.input_state.mouse_coords = position.to_owned();
+                if let Some(event) = self.pointer.pointer_moved(*position) {
+                    self.handle_drag(event);
+                }
+                self.set_hovered(self.widget_at(*position));
+                true
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.set_hovered(None);
+                true
+            }
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                let delta = match *delta {
+                    // Shift turns the wheel sideways
+                    MouseScrollDelta::LineDelta(x, y) if self.input_state.modifiers.shift_key() => {
+                        MouseScrollDelta::LineDelta(y, x)
+                    }
+                    delta => delta,
+                };
+                let position = self.input_state.mouse_coords;
+                if let Some((dx, dy)) = self.kinetic_scroll.scroll(position, delta, *phase) {
+                    self.scroll_at(position, dx, dy);
+                }
+                true
+            }
+            WindowEvent::Ime(ime) => {
+                self.components
+                    .iter_mut()
+                    .filter_map(Component::text_field_mut)
+                    .for_each(|text_field| match ime {
+                        Ime::Preedit(text, cursor) => {
+                            text_field.set_preedit(&mut self.font_system, text, *cursor)
+                        }
+                        Ime::Commit(text) => text_field.commit_preedit(&mut self.font_system, text),
+                        Ime::Disabled => text_field.cancel_preedit(&mut self.font_system),
+                        Ime::Enabled => (),
+                    });
+                self.validate_forms(None);
+                self.update_ime();
+                true
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.input_state.modifiers = modifiers.state();
                 true
             }
             WindowEvent::MouseInput { state, button, .. } => match state {
                 ElementState::Pressed => {
                     if button == &winit::event::MouseButton::Left && !self.input_state.clicked {
                         self.input_state.clicked = true;
+                        self.input_state.register_click();
                         self.handle_click();
                     }
                     true
@@ -309,6 +1000,7 @@ impl<'window> State<'window> {
                 ElementState::Released => {
                     if button == &winit::event::MouseButton::Left && self.input_state.clicked {
                         self.input_state.clicked = false;
+                        self.handle_release();
                     }
                     true
                 }
@@ -318,33 +1010,120 @@ impl<'window> State<'window> {
                     elwt.exit()
                 }
 
-                self.components
-                    .iter_mut()
-                    .filter_map(|component| match component {
-                        Component::TextField(_id, text_field) => {
-                            if text_field.active {
-                                Some(text_field)
-                            } else {
-                                None
-                            }
+                if event.state == ElementState::Pressed
+                    && event.key_without_modifiers() == Key::Named(NamedKey::Tab)
+                {
+                    self.focus_next(self.input_state.modifiers.shift_key());
+                    self.validate_forms(None);
+                    self.update_ime();
+                    return true;
+                }
+
+                if event.state == ElementState::Pressed && !event.repeat {
+                    let focused = self.focus.focused();
+                    let button = match event.key_without_modifiers().as_ref() {
+                        Key::Named(NamedKey::Enter | NamedKey::Space)
+                            if focused.is_some_and(|id| self.is_button(id)) =>
+                        {
+                            focused
+                        }
+                        Key::Named(NamedKey::Enter) => {
+                            focused.and_then(|id| self.default_button(id))
                         }
                         _ => None,
-                    })
+                    };
+                    if let Some(button_id) = button {
+                        self.activate_button(button_id);
+                        self.update_ime();
+                        return true;
+                    }
+                }
+
+                if event.state == ElementState::Pressed {
+                    if let (Some(focused), Key::Named(key)) =
+                        (self.focus.focused(), event.key_without_modifiers())
+                    {
+                        let scrolled =
+                            self.components.iter_mut().any(|component| match component {
+                                Component::ScrollView(Id(id), scroll_view) if *id == focused => {
+                                    scroll_view.handle_key(&key)
+                                }
+                                _ => false,
+                            });
+                        if scrolled {
+                            return true;
+                        }
+                    }
+                }
+
+                self.components
+                    .iter_mut()
+                    .filter_map(Component::text_field_mut)
+                    .filter(|text_field| text_field.active && text_field.preedit.is_none())
                     .for_each(|text_field| {
                         if event.state == ElementState::Pressed {
+                            let select = self.input_state.modifiers.shift_key();
+                            let by_word = self.input_state.word_key();
                             match event.key_without_modifiers().as_ref() {
                                 Key::Named(NamedKey::Backspace) => {
-                                    text_field.remove_character(&mut self.font_system);
+                                    text_field.remove_character(&mut self.font_system, by_word);
+                                }
+                                Key::Named(NamedKey::Delete) => {
+                                    text_field.delete_character(&mut self.font_system, by_word);
+                                }
+                                Key::Named(NamedKey::ArrowLeft) => {
+                                    text_field.move_cursor_left(select, by_word)
+                                }
+                                Key::Named(NamedKey::ArrowRight) => {
+                                    text_field.move_cursor_right(select, by_word)
+                                }
+                                Key::Named(NamedKey::Home) => {
+                                    text_field.move_cursor_to_start(select)
+                                }
+                                Key::Named(NamedKey::End) => text_field.move_cursor_to_end(select),
+                                Key::Named(NamedKey::ArrowUp) => text_field.move_cursor_up(select),
+                                Key::Named(NamedKey::ArrowDown) => {
+                                    text_field.move_cursor_down(select)
+                                }
+                                Key::Named(NamedKey::Enter) => {
+                                    text_field.insert_newline(&mut self.font_system);
+                                }
+                                Key::Character("a") if self.input_state.shortcut_key() => {
+                                    text_field.select_all();
+                                }
+                                Key::Character("z") if self.input_state.shortcut_key() => {
+                                    if self.input_state.modifiers.shift_key() {
+                                        text_field.redo(&mut self.font_system);
+                                    } else {
+                                        text_field.undo(&mut self.font_system);
+                                    }
+                                }
+                                Key::Character("y") if self.input_state.shortcut_key() => {
+                                    text_field.redo(&mut self.font_system);
+                                }
+                                Key::Character("c") if self.input_state.shortcut_key() => {
+                                    text_field.copy(self.clipboard.as_mut());
+                                }
+                                Key::Character("x") if self.input_state.shortcut_key() => {
+                                    text_field.cut(&mut self.font_system, self.clipboard.as_mut());
+                                }
+                                Key::Character("v") if self.input_state.shortcut_key() => {
+                                    text_field
+                                        .paste(&mut self.font_system, self.clipboard.as_mut());
                                 }
-                                Key::Named(NamedKey::Enter) => (),
                                 _ => {
                                     if let Some(ref txt) = event.text {
-                                        text_field.add_text(&mut self.font_system, txt.as_str());
+                                        if !self.input_state.shortcut_key() {
+                                            text_field
+                                                .add_text(&mut self.font_system, txt.as_str());
+                                        }
                                     }
                                 }
                             }
                         }
                     });
+                self.validate_forms(None);
+                self.update_ime();
                 true
             }
             _ => false,
@@ -358,35 +1137,125 @@ impl<'window> State<'window> {
 
         let mut num_vertices = 0;
         let mut num_indices = 0;
-        self.components
-            .iter_mut()
-            .for_each(|component| match component {
-                Component::Button(_id, button) => {
-                    let button_active = button.is_hovered(self.input_state.mouse_coords);
-                    let button_vertices = button.rectangle.vertices(button_active, self.size);
-
-                    vertices.extend_from_slice(&button_vertices);
-                    indices.extend_from_slice(&button.rectangle.indices(num_vertices));
+        let size = self.size;
+        self.components.iter_mut().for_each(|component| {
+            if self.focus.is_focus_visible(component.id()) {
+                push_rectangle(
+                    &mut vertices,
+                    &mut indices,
+                    &mut num_vertices,
+                    &mut num_indices,
+                    &mut focus::focus_ring(component.rect_pos()),
+                    false,
+                    size,
+                );
+            }
 
-                    num_vertices += button_vertices.len() as u16;
-                    num_indices += rectangle::NUM_INDICES;
+            match component {
+                Component::Button(Id(id), button) => {
+                    push_rectangle(
+                        &mut vertices,
+                        &mut indices,
+                        &mut num_vertices,
+                        &mut num_indices,
+                        &mut button.rectangle,
+                        self.hover.is_hovered(*id),
+                        size,
+                    );
 
                     text_areas.push(
                         button
                             .text
-                            .text_area(button_active && self.input_state.clicked),
+                            .text_area(button.is_pressed(self.input_state.mouse_coords)),
+                    );
+                }
+                Component::ScrollView(_id, scroll_view) => {
+                    push_rectangle(
+                        &mut vertices,
+                        &mut indices,
+                        &mut num_vertices,
+                        &mut num_indices,
+                        &mut scroll_view.rectangle,
+                        false,
+                        size,
                     );
+                    for mut scrollbar in scroll_view.scrollbars() {
+                        push_rectangle(
+                            &mut vertices,
+                            &mut indices,
+                            &mut num_vertices,
+                            &mut num_indices,
+                            &mut scrollbar,
+                            false,
+                            size,
+                        );
+                    }
+
+                    text_areas.push(scroll_view.content.text_area(false));
                 }
-                Component::TextField(_id, text_field) => {
+                Component::Text(_id, text) => {
+                    for mut decoration in text.decorations() {
+                        push_rectangle(
+                            &mut vertices,
+                            &mut indices,
+                            &mut num_vertices,
+                            &mut num_indices,
+                            &mut decoration,
+                            false,
+                            size,
+                        );
+                    }
+
+                    text_areas.push(text.text_area(false));
+                }
+                component => {
+                    let Some(text_field) = component.text_field_mut() else {
+                        return;
+                    };
                     let text_field_active = text_field.active;
-                    let text_field_vertices =
-                        text_field.rectangle.vertices(text_field_active, self.size);
+                    let text_field_vertices = push_rectangle(
+                        &mut vertices,
+                        &mut indices,
+                        &mut num_vertices,
+                        &mut num_indices,
+                        &mut text_field.rectangle,
+                        text_field_active,
+                        size,
+                    );
+                    if text_field.error.is_some() {
+                        text_field_vertices
+                            .iter_mut()
+                            .for_each(|vertex| vertex.border_color = text_field::ERROR_COLOR);
+                    }
 
-                    vertices.extend_from_slice(&text_field_vertices);
-                    indices.extend_from_slice(&text_field.rectangle.indices(num_vertices));
+                    let password_revealed = text_field.password_revealed;
+                    if let Some(ref mut toggle) = text_field.password_toggle {
+                        push_rectangle(
+                            &mut vertices,
+                            &mut indices,
+                            &mut num_vertices,
+                            &mut num_indices,
+                            toggle,
+                            password_revealed,
+                            size,
+                        );
+                    }
 
-                    num_vertices += text_field_vertices.len() as u16;
-                    num_indices += rectangle::NUM_INDICES;
+                    let highlights = text_field
+                        .get_selection()
+                        .into_iter()
+                        .chain(text_field.get_preedit_underline());
+                    for mut highlight in highlights {
+                        push_rectangle(
+                            &mut vertices,
+                            &mut indices,
+                            &mut num_vertices,
+                            &mut num_indices,
+                            &mut highlight,
+                            false,
+                            size,
+                        );
+                    }
 
                     let now = SystemTime::now();
                     if text_field_active
@@ -396,14 +1265,15 @@ impl<'window> State<'window> {
                             })
                         })
                     {
-                        let mut cursor = text_field.get_cursor();
-                        let cursor_vertices = cursor.vertices(false, self.size);
-
-                        vertices.extend_from_slice(&cursor_vertices);
-                        indices.extend_from_slice(&text_field.get_cursor().indices(num_vertices));
-
-                        num_vertices += cursor_vertices.len() as u16;
-                        num_indices += rectangle::NUM_INDICES;
+                        push_rectangle(
+                            &mut vertices,
+                            &mut indices,
+                            &mut num_vertices,
+                            &mut num_indices,
+                            &mut text_field.get_cursor(),
+                            false,
+                            size,
+                        );
 
                         if text_field.last_cursor_blink.is_some_and(|dur| {
                             now.duration_since(dur).is_ok_and(|duration| {
@@ -416,12 +1286,15 @@ impl<'window> State<'window> {
 
                     text_areas.push(
                         text_field
-                            .text
+                            .visible_text()
                             .text_area(text_field_active && self.input_state.clicked),
                     );
+                    if let Some(ref error) = text_field.error {
+                        text_areas.push(error.text_area(false));
+                    }
                 }
-                Component::Text(_id, text) => text_areas.push(text.text_area(false)),
-            });
+            }
+        });
 
         let vertex_buffer = self
             .device
@@ -503,12 +1376,37 @@ impl<'window> State<'window> {
     }
 }
 
+// appends the rectangle to the geometry drawn this frame, returning its vertices so callers can
+// adjust them
+fn push_rectangle<'a>(
+    vertices: &'a mut Vec<Vertex>,
+    indices: &mut Vec<u16>,
+    num_vertices: &mut u16,
+    num_indices: &mut u32,
+    rect: &mut Rectangle,
+    active: bool,
+    size: PhysicalSize<u32>,
+) -> &'a mut [Vertex] {
+    let rect_vertices = rect.vertices(active, size);
+    let start = vertices.len();
+
+    vertices.extend_from_slice(&rect_vertices);
+    indices.extend_from_slice(&rect.indices(*num_vertices));
+
+    *num_vertices += rect_vertices.len() as u16;
+    *num_indices += rectangle::NUM_INDICES;
+    &mut vertices[start..]
+}
+
 fn main() {
     env_logger::init();
     let event_loop = EventLoopBuilder::<GUIEvent>::with_user_event()
         .build()
         .unwrap();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let window = WindowBuilder::new()
+        .with_inner_size(PhysicalSize::new(1150, 600))
+        .build(&event_loop)
+        .unwrap();
     pollster::block_on(run(event_loop, window));
 }
 
@@ -526,32 +1424,62 @@ fn handle_success_event(state: &mut State, ev: &GUIEvent) {
             .components
             .iter()
             .filter_map(|component| match component {
-                Component::TextField(Id(id), text_field)
-                    if id == target_id && !text_field.content.is_empty() =>
-                {
-                    Some(text_field)
-                }
+                Component::TextField(Id(id), text_field) if id == target_id => Some(text_field),
                 _ => None,
             })
             .next(),
     };
 
     if let Some(text_field) = comp {
-        state.components.push(Component::Text(
-            Id(2),
-            text::Text::new(
-                &mut state.font_system,
-                RectPos {
-                    top: 250,
-                    left: 100,
-                    bottom: 400,
-                    right: 400,
+        let name = text_field.content.clone();
+        let style = text::TextStyle {
+            italic: true,
+            align: text::TextAlign::Center,
+            overflow: text::TextOverflow::Wrap,
+            ..Default::default()
+        };
+        // only take up as much of the space below the button as the message needs
+        let measurement = state.text_measurer.measure(
+            &mut state.font_system,
+            &format!("Success: {name}!"),
+            &style,
+            Some(300.0),
+        );
+        let mut success_text = text::Text::new(
+            &mut state.font_system,
+            RectPos {
+                top: 280,
+                left: 100,
+                bottom: 280 + (measurement.height.ceil() as u32).min(150),
+                right: 400,
+            },
+            "",
+            Color::rgb(0, 200, 0),
+            Color::rgb(0, 200, 0),
+            style,
+        );
+        success_text.set_rich_text(
+            &mut state.font_system,
+            vec![
+                text::TextSpan {
+                    text: "Success: ".to_string(),
+                    ..Default::default()
                 },
-                &format!("Success: {}!", text_field.content),
-                Color::rgb(0, 200, 0),
-                Color::rgb(0, 200, 0),
-            ),
-        ));
+                text::TextSpan {
+                    text: name,
+                    color: Some(Color::rgb(0, 120, 0)),
+                    weight: Some(Weight::BOLD),
+                    italic: Some(false),
+                    underline: true,
+                    ..Default::default()
+                },
+                text::TextSpan {
+                    text: "!".to_string(),
+                    ..Default::default()
+                },
+            ],
+        );
+        state.components.push(Component::Text(Id(2), success_text));
     }
 }
 
@@ -596,6 +1524,7 @@ async fn run(event_loop: EventLoop<GUIEvent>, window: Window) {
                 };
             }
             Event::AboutToWait => {
+                state.update_scroll();
                 state.window.request_redraw();
             }
             _ => (),
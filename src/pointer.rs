@@ -0,0 +1,92 @@
+use winit::dpi::PhysicalPosition;
+
+/// Distance in pixels the pointer has to travel while pressed before a drag starts, so a slightly
+/// shaky click is not taken for a drag.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragPhase {
+    Start,
+    Move,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEvent {
+    pub id: usize,
+    pub phase: DragPhase,
+    pub position: PhysicalPosition<f64>,
+    /// Movement since the previous event of the drag.
+    pub delta: (f64, f64),
+    /// Movement since the press that started the drag.
+    pub offset: (f64, f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Capture {
+    id: usize,
+    origin: PhysicalPosition<f64>,
+    last_position: PhysicalPosition<f64>,
+    dragging: bool,
+}
+
+/// Routes pointer movement to the widget that was pressed, until the press is released, even
+/// when the pointer leaves the widget's rectangle.
+#[derive(Debug, Default)]
+pub struct PointerCapture {
+    capture: Option<Capture>,
+}
+
+impl PointerCapture {
+    pub fn captured(&self) -> Option<usize> {
+        self.capture.map(|capture| capture.id)
+    }
+
+    pub fn capture(&mut self, id: usize, position: PhysicalPosition<f64>) {
+        self.capture = Some(Capture {
+            id,
+            origin: position,
+            last_position: position,
+            dragging: false,
+        });
+    }
+
+    pub fn pointer_moved(&mut self, position: PhysicalPosition<f64>) -> Option<DragEvent> {
+        let capture = self.capture.as_mut()?;
+        let phase = if capture.dragging {
+            DragPhase::Move
+        } else {
+            let (dx, dy) = (position.x - capture.origin.x, position.y - capture.origin.y);
+            if dx.hypot(dy) < DRAG_THRESHOLD {
+                return None;
+            }
+            capture.dragging = true;
+            DragPhase::Start
+        };
+
+        let event = drag_event(capture, phase, position);
+        capture.last_position = position;
+        Some(event)
+    }
+
+    /// Ends the capture, returning the end of the drag if the pointer was dragged.
+    pub fn release(&mut self, position: PhysicalPosition<f64>) -> Option<DragEvent> {
+        let capture = self.capture.take()?;
+        capture
+            .dragging
+            .then(|| drag_event(&capture, DragPhase::End, position))
+    }
+}
+
+fn drag_event(capture: &Capture, phase: DragPhase, position: PhysicalPosition<f64>) -> DragEvent {
+    DragEvent {
+        id: capture.id,
+        phase,
+        position,
+        delta: (
+            position.x - capture.last_position.x,
+            position.y - capture.last_position.y,
+        ),
+        offset: (position.x - capture.origin.x, position.y - capture.origin.y),
+    }
+}
@@ -20,6 +20,15 @@ pub struct Rectangle {
 
 pub const NUM_INDICES: u32 = 6;
 
+impl RectPos {
+    pub fn contains(&self, mouse_coords: PhysicalPosition<f64>) -> bool {
+        mouse_coords.x > self.left as f64
+            && mouse_coords.x < self.right as f64
+            && mouse_coords.y > self.top as f64
+            && mouse_coords.y < self.bottom as f64
+    }
+}
+
 impl Rectangle {
     pub fn new(
         position: RectPos,
@@ -105,10 +114,6 @@ impl Rectangle {
     }
 
     pub fn is_hovered(&self, mouse_coords: PhysicalPosition<f64>) -> bool {
-        let rect_pos = self.position;
-        mouse_coords.x > rect_pos.left as f64
-            && mouse_coords.x < rect_pos.right as f64
-            && mouse_coords.y > rect_pos.top as f64
-            && mouse_coords.y < rect_pos.bottom as f64
+        self.position.contains(mouse_coords)
     }
 }
@@ -0,0 +1,129 @@
+use std::time::Instant;
+use winit::dpi::PhysicalPosition;
+use winit::event::{MouseScrollDelta, TouchPhase};
+
+/// Pixels scrolled for each line of a wheel notch.
+pub const LINE_SCROLL_PIXELS: f32 = 40.0;
+// seconds for a wheel scroll to cover ~63% of the remaining distance
+const SMOOTH_SCROLL_TIME: f32 = 0.05;
+// fraction of a fling's velocity lost per second is 1 - e^-FLING_FRICTION
+const FLING_FRICTION: f32 = 4.0;
+// pixels per second
+const MIN_FLING_VELOCITY: f32 = 100.0;
+// longer gaps between trackpad events don't count towards the fling velocity, nor do long frames
+const MAX_STEP_TIME: f32 = 0.1;
+const MIN_SCROLL_STEP: f32 = 0.5;
+
+pub trait Scrollable {
+    /// Scrolls the content by up to `dx`/`dy` pixels, returning how far it actually scrolled.
+    fn scroll_by(&mut self, dx: f32, dy: f32) -> (f32, f32);
+}
+
+/// Turns wheel and trackpad events into pixel offsets to scroll by. Wheel notches are spread over
+/// a few frames, and trackpad gestures keep scrolling with decaying speed after they end.
+#[derive(Debug, Default)]
+pub struct KineticScroll {
+    position: PhysicalPosition<f64>,
+    remaining: (f32, f32),
+    velocity: (f32, f32),
+    flinging: bool,
+    last_event: Option<Instant>,
+    last_step: Option<Instant>,
+}
+
+impl KineticScroll {
+    /// Handles a scroll event at the pointer `position`, returning the offset to scroll by right
+    /// away, as opposed to the one animated by `step`. Positive offsets scroll the content towards
+    /// its end.
+    pub fn scroll(
+        &mut self,
+        position: PhysicalPosition<f64>,
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    ) -> Option<(f32, f32)> {
+        let now = Instant::now();
+        let since_last_event = self
+            .last_event
+            .map(|last_event| now.duration_since(last_event).as_secs_f32());
+        self.last_event = Some(now);
+        self.position = position;
+
+        match delta {
+            MouseScrollDelta::LineDelta(x, y) => {
+                self.stop_fling();
+                self.remaining.0 -= x * LINE_SCROLL_PIXELS;
+                self.remaining.1 -= y * LINE_SCROLL_PIXELS;
+                None
+            }
+            MouseScrollDelta::PixelDelta(delta) => {
+                let offset = (-delta.x as f32, -delta.y as f32);
+                match phase {
+                    TouchPhase::Started | TouchPhase::Cancelled => self.stop(),
+                    TouchPhase::Moved => {
+                        self.velocity = match since_last_event {
+                            Some(dt) if dt > 0.0 && dt < MAX_STEP_TIME => {
+                                (offset.0 / dt, offset.1 / dt)
+                            }
+                            _ => (0.0, 0.0),
+                        };
+                    }
+                    TouchPhase::Ended => {
+                        self.flinging = self.velocity.0.hypot(self.velocity.1) > MIN_FLING_VELOCITY;
+                    }
+                }
+                Some(offset)
+            }
+        }
+    }
+
+    /// Advances the animation, returning the pointer position of the scroll and the offset to
+    /// scroll by in this frame.
+    pub fn step(&mut self, now: Instant) -> Option<(PhysicalPosition<f64>, (f32, f32))> {
+        if !self.flinging && self.remaining == (0.0, 0.0) {
+            self.last_step = None;
+            return None;
+        }
+        let dt = self.last_step.map_or(0.0, |last_step| {
+            now.duration_since(last_step)
+                .as_secs_f32()
+                .min(MAX_STEP_TIME)
+        });
+        self.last_step = Some(now);
+
+        let progress = 1.0 - (-dt / SMOOTH_SCROLL_TIME).exp();
+        let mut offset = (0.0, 0.0);
+        for (offset, remaining) in [
+            (&mut offset.0, &mut self.remaining.0),
+            (&mut offset.1, &mut self.remaining.1),
+        ] {
+            *offset = if remaining.abs() < MIN_SCROLL_STEP {
+                *remaining
+            } else {
+                *remaining * progress
+            };
+            *remaining -= *offset;
+        }
+
+        if self.flinging {
+            offset.0 += self.velocity.0 * dt;
+            offset.1 += self.velocity.1 * dt;
+            let friction = (-FLING_FRICTION * dt).exp();
+            self.velocity = (self.velocity.0 * friction, self.velocity.1 * friction);
+            if self.velocity.0.hypot(self.velocity.1) < MIN_FLING_VELOCITY {
+                self.stop_fling();
+            }
+        }
+        Some((self.position, offset))
+    }
+
+    /// Stops any ongoing animation, e.g. when the content can't scroll any further.
+    pub fn stop(&mut self) {
+        self.remaining = (0.0, 0.0);
+        self.stop_fling();
+    }
+
+    fn stop_fling(&mut self) {
+        self.flinging = false;
+        self.velocity = (0.0, 0.0);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::rectangle::{RectPos, Rectangle};
+use crate::scroll::{Scrollable, LINE_SCROLL_PIXELS};
+use crate::text::{Text, TextStyle};
+use glyphon::{Color, FontSystem};
+use std::time::Instant;
+use winit::dpi::PhysicalPosition;
+use winit::keyboard::NamedKey;
+use winit::window::CursorIcon;
+
+const SCROLLBAR_WIDTH: u32 = 6;
+const SCROLLBAR_MARGIN: u32 = 3;
+// the content is inset so the scrollbars don't cover it
+const PADDING: u32 = SCROLLBAR_WIDTH + 2 * SCROLLBAR_MARGIN;
+const MIN_THUMB_LENGTH: f32 = 20.0;
+const SCROLLBAR_COLOR: [f32; 3] = [0.4, 0.4, 0.4];
+// seconds the scrollbars stay after scrolling or the pointer leaving, before fading out
+const SCROLLBAR_HIDE_DELAY: f32 = 1.0;
+const SCROLLBAR_FADE_TIME: f32 = 0.3;
+
+pub struct ScrollViewConfig {
+    pub rect_pos: RectPos,
+    pub fill_color: [f32; 3],
+    pub border_color: [f32; 3],
+    pub content: String,
+    pub text_color: Color,
+    pub text_style: TextStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DragTarget {
+    Content,
+    VerticalThumb,
+    HorizontalThumb,
+}
+
+/// Shows text larger than its rectangle, clipped to it and scrolled in both directions. Long
+/// lines are not wrapped.
+#[derive(Debug)]
+pub struct ScrollView {
+    pub rectangle: Rectangle,
+    pub content: Text,
+    fill_color: [f32; 3],
+    hovered: bool,
+    last_shown: Option<Instant>,
+    drag_target: Option<DragTarget>,
+}
+
+impl ScrollView {
+    pub fn new(cfg: ScrollViewConfig, font_system: &mut FontSystem) -> Self {
+        let mut content = Text::new_multiline(
+            font_system,
+            RectPos {
+                top: cfg.rect_pos.top + PADDING,
+                left: cfg.rect_pos.left + PADDING,
+                bottom: cfg.rect_pos.bottom - PADDING,
+                right: cfg.rect_pos.right - PADDING,
+            },
+            &cfg.content,
+            cfg.text_color,
+            cfg.text_color,
+            cfg.text_style,
+        );
+        content.set_wrap(font_system, false);
+
+        Self {
+            rectangle: Rectangle::new(
+                cfg.rect_pos,
+                cfg.fill_color,
+                cfg.fill_color,
+                cfg.border_color,
+                cfg.border_color,
+            ),
+            content,
+            fill_color: cfg.fill_color,
+            hovered: false,
+            last_shown: None,
+            drag_target: None,
+        }
+    }
+
+    /// Replaces the shown text, keeping the scroll position as far as the new text allows.
+    pub fn set_content(&mut self, font_system: &mut FontSystem, content: &str) {
+        let (scroll_left, scroll_top) = (self.content.scroll_left(), self.content.scroll_top());
+        self.content.set_text(font_system, content);
+        self.content.set_scroll_left(scroll_left);
+        self.content.set_scroll_top(scroll_top);
+    }
+
+    pub fn is_hovered(&self, mouse_coords: PhysicalPosition<f64>) -> bool {
+        self.rectangle.is_hovered(mouse_coords)
+    }
+
+    pub fn set_hovered(&mut self, hovered: bool) {
+        if self.hovered && !hovered {
+            self.last_shown = Some(Instant::now());
+        }
+        self.hovered = hovered;
+    }
+
+    pub fn cursor_icon(&self) -> CursorIcon {
+        match self.drag_target {
+            Some(DragTarget::Content) => CursorIcon::Grabbing,
+            _ => CursorIcon::Default,
+        }
+    }
+
+    /// Starts a drag of a scrollbar thumb, or of the content anywhere else.
+    pub fn press(&mut self, mouse_coords: PhysicalPosition<f64>) {
+        let hits = |thumb: Option<RectPos>| thumb.is_some_and(|thumb| thumb.contains(mouse_coords));
+        self.drag_target = Some(if hits(self.vertical_thumb()) {
+            DragTarget::VerticalThumb
+        } else if hits(self.horizontal_thumb()) {
+            DragTarget::HorizontalThumb
+        } else {
+            DragTarget::Content
+        });
+    }
+
+    // thumbs move with the pointer, the content against it
+    pub fn drag(&mut self, delta: (f64, f64)) {
+        let (dx, dy) = (delta.0 as f32, delta.1 as f32);
+        match self.drag_target {
+            Some(DragTarget::Content) => {
+                self.scroll_by(-dx, -dy);
+            }
+            Some(DragTarget::VerticalThumb) => {
+                let (track_length, thumb_length) = self.vertical_track();
+                let scale = self.max_scroll_top() / (track_length - thumb_length).max(1.0);
+                self.scroll_by(0.0, dy * scale);
+            }
+            Some(DragTarget::HorizontalThumb) => {
+                let (track_length, thumb_length) = self.horizontal_track();
+                let (min_scroll, max_scroll) = self.scroll_left_range();
+                let scale = (max_scroll - min_scroll) / (track_length - thumb_length).max(1.0);
+                self.scroll_by(dx * scale, 0.0);
+            }
+            None => (),
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.drag_target = None;
+    }
+
+    /// Scrolls with the arrow, page and Home/End keys, returning whether the key was handled.
+    pub fn handle_key(&mut self, key: &NamedKey) -> bool {
+        let page = self.content.visible_height();
+        let (dx, dy) = match key {
+            NamedKey::ArrowUp => (0.0, -LINE_SCROLL_PIXELS),
+            NamedKey::ArrowDown => (0.0, LINE_SCROLL_PIXELS),
+            NamedKey::ArrowLeft => (-LINE_SCROLL_PIXELS, 0.0),
+            NamedKey::ArrowRight => (LINE_SCROLL_PIXELS, 0.0),
+            NamedKey::PageUp => (0.0, -page),
+            NamedKey::PageDown => (0.0, page),
+            NamedKey::Home => (0.0, -self.content.scroll_top()),
+            NamedKey::End => (0.0, self.max_scroll_top() - self.content.scroll_top()),
+            _ => return false,
+        };
+        self.scroll_by(dx, dy);
+        true
+    }
+
+    /// Returns the scrollbar thumbs of the overflowing directions. They show while the pointer
+    /// is over the view and for a moment after scrolling or leaving, then fade out.
+    pub fn scrollbars(&self) -> Vec<Rectangle> {
+        let opacity = if self.hovered || self.drag_target.is_some() {
+            1.0
+        } else {
+            self.last_shown.map_or(0.0, |last_shown| {
+                let hidden_for = last_shown.elapsed().as_secs_f32() - SCROLLBAR_HIDE_DELAY;
+                (1.0 - hidden_for / SCROLLBAR_FADE_TIME).clamp(0.0, 1.0)
+            })
+        };
+        if opacity == 0.0 {
+            return Vec::new();
+        }
+
+        let mut color = self.fill_color;
+        color
+            .iter_mut()
+            .zip(SCROLLBAR_COLOR)
+            .for_each(|(color, scrollbar_color)| {
+                *color += (scrollbar_color - *color) * opacity;
+            });
+        self.vertical_thumb()
+            .into_iter()
+            .chain(self.horizontal_thumb())
+            .map(|thumb| Rectangle::new(thumb, color, color, color, color))
+            .collect()
+    }
+
+    fn max_scroll_top(&self) -> f32 {
+        (self.content.content_height() - self.content.visible_height()).max(0.0)
+    }
+
+    fn scroll_left_range(&self) -> (f32, f32) {
+        let (min_x, max_x) = self.content.horizontal_extent();
+        (min_x, max_x - self.content.visible_width())
+    }
+
+    fn overflows_vertically(&self) -> bool {
+        self.max_scroll_top() > 0.0
+    }
+
+    fn overflows_horizontally(&self) -> bool {
+        let (min_scroll, max_scroll) = self.scroll_left_range();
+        max_scroll > min_scroll
+    }
+
+    // the lengths of the track and of the thumb on it, which is as long relative to the track as
+    // the visible part is relative to the content
+    fn vertical_track(&self) -> (f32, f32) {
+        let rect_pos = self.rectangle.position;
+        let mut track_length = (rect_pos.bottom - rect_pos.top - 2 * SCROLLBAR_MARGIN) as f32;
+        if self.overflows_horizontally() {
+            track_length -= (SCROLLBAR_WIDTH + SCROLLBAR_MARGIN) as f32;
+        }
+        let visible = self.content.visible_height() / self.content.content_height();
+        (
+            track_length,
+            (track_length * visible).clamp(MIN_THUMB_LENGTH.min(track_length), track_length),
+        )
+    }
+
+    fn horizontal_track(&self) -> (f32, f32) {
+        let rect_pos = self.rectangle.position;
+        let mut track_length = (rect_pos.right - rect_pos.left - 2 * SCROLLBAR_MARGIN) as f32;
+        if self.overflows_vertically() {
+            track_length -= (SCROLLBAR_WIDTH + SCROLLBAR_MARGIN) as f32;
+        }
+        let (min_x, max_x) = self.content.horizontal_extent();
+        let visible = self.content.visible_width() / (max_x - min_x);
+        (
+            track_length,
+            (track_length * visible).clamp(MIN_THUMB_LENGTH.min(track_length), track_length),
+        )
+    }
+
+    fn vertical_thumb(&self) -> Option<RectPos> {
+        if !self.overflows_vertically() {
+            return None;
+        }
+        let rect_pos = self.rectangle.position;
+        let (track_length, thumb_length) = self.vertical_track();
+        let progress = self.content.scroll_top() / self.max_scroll_top();
+        let top =
+            (rect_pos.top + SCROLLBAR_MARGIN) as f32 + (track_length - thumb_length) * progress;
+        Some(RectPos {
+            top: top as u32,
+            left: rect_pos.right - SCROLLBAR_MARGIN - SCROLLBAR_WIDTH,
+            bottom: (top + thumb_length) as u32,
+            right: rect_pos.right - SCROLLBAR_MARGIN,
+        })
+    }
+
+    fn horizontal_thumb(&self) -> Option<RectPos> {
+        if !self.overflows_horizontally() {
+            return None;
+        }
+        let rect_pos = self.rectangle.position;
+        let (track_length, thumb_length) = self.horizontal_track();
+        let (min_scroll, max_scroll) = self.scroll_left_range();
+        let progress = (self.content.scroll_left() - min_scroll) / (max_scroll - min_scroll);
+        let left =
+            (rect_pos.left + SCROLLBAR_MARGIN) as f32 + (track_length - thumb_length) * progress;
+        Some(RectPos {
+            top: rect_pos.bottom - SCROLLBAR_MARGIN - SCROLLBAR_WIDTH,
+            left: left as u32,
+            bottom: rect_pos.bottom - SCROLLBAR_MARGIN,
+            right: (left + thumb_length) as u32,
+        })
+    }
+}
+
+impl Scrollable for ScrollView {
+    fn scroll_by(&mut self, dx: f32, dy: f32) -> (f32, f32) {
+        let (scroll_left, scroll_top) = (self.content.scroll_left(), self.content.scroll_top());
+        self.content.set_scroll_left(scroll_left + dx);
+        self.content.set_scroll_top(scroll_top + dy);
+
+        let scrolled = (
+            self.content.scroll_left() - scroll_left,
+            self.content.scroll_top() - scroll_top,
+        );
+        if scrolled != (0.0, 0.0) {
+            self.last_shown = Some(Instant::now());
+        }
+        scrolled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::bundled_font_system;
+
+    fn scroll_view(font_system: &mut FontSystem, lines: usize) -> ScrollView {
+        ScrollView::new(
+            ScrollViewConfig {
+                rect_pos: RectPos {
+                    top: 0,
+                    left: 0,
+                    bottom: 200,
+                    right: 300,
+                },
+                fill_color: [1.0, 1.0, 1.0],
+                border_color: [0.0, 0.0, 0.0],
+                content: "line\n".repeat(lines),
+                text_color: Color::rgb(0, 0, 0),
+                text_style: TextStyle::default(),
+            },
+            font_system,
+        )
+    }
+
+    #[test]
+    fn set_content_keeps_scroll_position() {
+        let mut font_system = bundled_font_system();
+        let mut view = scroll_view(&mut font_system, 100);
+        view.scroll_by(0.0, 50.0);
+
+        view.set_content(&mut font_system, &"other line\n".repeat(100));
+        assert_eq!(view.content.scroll_top(), 50.0);
+    }
+
+    #[test]
+    fn set_content_clamps_scroll_to_shorter_content() {
+        let mut font_system = bundled_font_system();
+        let mut view = scroll_view(&mut font_system, 100);
+        view.scroll_by(1000.0, 1000.0);
+
+        view.set_content(&mut font_system, "short");
+        assert_eq!(view.content.scroll_top(), 0.0);
+        assert_eq!(view.content.scroll_left(), 0.0);
+        assert!(view.scrollbars().is_empty());
+    }
+}
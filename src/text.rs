@@ -1,10 +1,75 @@
-use crate::rectangle::RectPos;
-use glyphon::{Attrs, Buffer, Color, Family, FontSystem, Metrics, Shaping, TextArea, TextBounds};
+use crate::rectangle::{RectPos, Rectangle};
+use glyphon::{
+    cosmic_text::{Align, BidiParagraphs},
+    Attrs, AttrsList, Buffer, BufferLine, Color, Cursor, Family, FontSystem, Metrics, Shaping,
+    Style, TextArea, TextBounds, Weight,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use winit::dpi::PhysicalPosition;
 
-#[derive(Debug)]
-pub struct TextWidth {
-    pub width: f32,
-    pub buffer_width: f32,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Start,
+    Center,
+    End,
+    Justify,
+}
+
+/// The direction `TextAlign::Start` and `TextAlign::End` refer to. `Auto` uses the base
+/// direction of each line's content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextDirection {
+    #[default]
+    Auto,
+    LeftToRight,
+    RightToLeft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlign {
+    Top,
+    #[default]
+    Middle,
+    Bottom,
+}
+
+/// How single-line text that is wider than its rectangle is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextOverflow {
+    #[default]
+    Clip,
+    Ellipsis,
+    EllipsisMiddle,
+    Wrap,
+    ShrinkToFit {
+        min_font_size: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    pub font_size: f32,
+    pub line_height: f32,
+    pub family: Family<'static>,
+    pub weight: Weight,
+    pub italic: bool,
+    pub align: TextAlign,
+    pub vertical_align: VerticalAlign,
+    pub overflow: TextOverflow,
+    pub direction: TextDirection,
+}
+
+/// A run of text with its own color and font attributes, overriding the `TextStyle` of the
+/// `Text` it is part of.
+#[derive(Debug, Clone, Default)]
+pub struct TextSpan {
+    pub text: String,
+    pub color: Option<Color>,
+    pub weight: Option<Weight>,
+    pub italic: Option<bool>,
+    pub underline: bool,
+    pub strikethrough: bool,
 }
 
 #[derive(Debug)]
@@ -13,10 +78,72 @@ pub struct Text {
     rect_pos: RectPos,
     color: Color,
     color_active: Color,
+    style: TextStyle,
+    spans: Vec<TextSpan>,
+    multiline: bool,
+    scroll_top: f32,
+    scroll_left: f32,
+    // byte offset of each buffer line in the text
+    line_starts: Vec<usize>,
 }
 
 const FONT_SIZE: f32 = 30.0;
 const LINE_HEIGHT: f32 = 42.0;
+// offsets from the baseline and thickness of text decorations, relative to the font size
+const UNDERLINE_OFFSET: f32 = 0.1;
+const STRIKETHROUGH_OFFSET: f32 = -0.3;
+const DECORATION_THICKNESS: f32 = 0.06;
+const ELLIPSIS: &str = "…";
+const SHRINK_STEP: f32 = 1.0;
+// multi-line buffers are laid out completely and scrolled by offsetting the text area instead
+pub const MULTILINE_BUFFER_HEIGHT: f32 = 1_000_000.0;
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            font_size: FONT_SIZE,
+            line_height: LINE_HEIGHT,
+            family: Family::SansSerif,
+            weight: Weight::NORMAL,
+            italic: false,
+            align: TextAlign::Start,
+            vertical_align: VerticalAlign::Middle,
+            overflow: TextOverflow::Clip,
+            direction: TextDirection::Auto,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn attrs(&self) -> Attrs<'static> {
+        Attrs::new()
+            .family(self.family)
+            .weight(self.weight)
+            .style(if self.italic {
+                Style::Italic
+            } else {
+                Style::Normal
+            })
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics::new(self.font_size, self.line_height)
+    }
+
+    // `None` and `End` follow the base direction of each line
+    fn cosmic_align(&self) -> Option<Align> {
+        match (self.align, self.direction) {
+            (TextAlign::Start, TextDirection::Auto) => None,
+            (TextAlign::Start, TextDirection::LeftToRight)
+            | (TextAlign::End, TextDirection::RightToLeft) => Some(Align::Left),
+            (TextAlign::Start, TextDirection::RightToLeft)
+            | (TextAlign::End, TextDirection::LeftToRight) => Some(Align::Right),
+            (TextAlign::End, TextDirection::Auto) => Some(Align::End),
+            (TextAlign::Center, _) => Some(Align::Center),
+            (TextAlign::Justify, _) => Some(Align::Justified),
+        }
+    }
+}
 
 impl Text {
     pub fn new(
@@ -25,8 +152,9 @@ impl Text {
         text: &str,
         color: Color,
         color_active: Color,
+        style: TextStyle,
     ) -> Self {
-        let mut buffer = Buffer::new(font_system, Metrics::new(FONT_SIZE, LINE_HEIGHT));
+        let mut buffer = Buffer::new(font_system, style.metrics());
 
         buffer.set_size(
             font_system,
@@ -34,51 +162,484 @@ impl Text {
             (rect_pos.bottom - rect_pos.top) as f32,
         );
 
-        buffer.set_text(
-            font_system,
-            text,
-            Attrs::new().family(Family::SansSerif),
-            Shaping::Advanced,
-        );
+        let wrap = match style.overflow {
+            TextOverflow::Wrap => glyphon::Wrap::Word,
+            _ => glyphon::Wrap::None,
+        };
+        buffer.set_wrap(font_system, wrap);
 
-        buffer.lines.iter_mut().for_each(|line| {
-            line.set_align(Some(glyphon::cosmic_text::Align::Center));
-        });
+        let mut text_line = Self {
+            buffer,
+            rect_pos,
+            color,
+            color_active,
+            style,
+            spans: Vec::new(),
+            multiline: false,
+            scroll_top: 0.0,
+            scroll_left: 0.0,
+            line_starts: vec![0],
+        };
+        text_line.set_text(font_system, text);
+        text_line
+    }
 
-        buffer.set_wrap(font_system, glyphon::Wrap::None);
+    pub fn new_multiline(
+        font_system: &mut FontSystem,
+        rect_pos: RectPos,
+        text: &str,
+        color: Color,
+        color_active: Color,
+        style: TextStyle,
+    ) -> Self {
+        let mut buffer = Buffer::new(font_system, style.metrics());
+
+        buffer.set_size(
+            font_system,
+            (rect_pos.right - rect_pos.left) as f32,
+            MULTILINE_BUFFER_HEIGHT,
+        );
 
-        buffer.shape_until_scroll(font_system);
+        buffer.set_wrap(font_system, glyphon::Wrap::Word);
 
-        Self {
+        let mut multiline_text = Self {
             buffer,
             rect_pos,
             color,
             color_active,
+            style,
+            spans: Vec::new(),
+            multiline: true,
+            scroll_top: 0.0,
+            scroll_left: 0.0,
+            line_starts: vec![0],
+        };
+        multiline_text.set_text(font_system, text);
+        multiline_text
+    }
+
+    /// Multi-line text wraps at word boundaries by default, without it long lines overflow and
+    /// can be scrolled horizontally.
+    pub fn set_wrap(&mut self, font_system: &mut FontSystem, wrap: bool) {
+        let wrap = if wrap {
+            glyphon::Wrap::Word
+        } else {
+            glyphon::Wrap::None
+        };
+        self.buffer.set_wrap(font_system, wrap);
+        self.buffer.shape_until_scroll(font_system);
+    }
+
+    pub fn set_text(&mut self, font_system: &mut FontSystem, text: &str) {
+        self.spans.clear();
+        match self.style.overflow {
+            TextOverflow::Ellipsis | TextOverflow::EllipsisMiddle if !self.multiline => {
+                let ellipsized = self.ellipsize(font_system, text);
+                self.shape_text(font_system, &ellipsized);
+            }
+            _ => self.shape_text(font_system, text),
         }
+        self.shrink_to_fit(font_system);
     }
 
-    pub fn get_text_width(&self) -> TextWidth {
-        TextWidth {
-            width: self
-                .buffer
-                .layout_runs()
-                .fold(0.0, |width, run| run.line_w.max(width)),
-            buffer_width: self.buffer.size().0,
+    fn shape_text(&mut self, font_system: &mut FontSystem, text: &str) {
+        let attrs = self.style.attrs();
+        self.buffer
+            .set_text(font_system, text, attrs, Shaping::Advanced);
+        let trailing_break = self.set_line_starts(text);
+
+        // cosmic-text drops a trailing line break, but the caret needs the empty line after it
+        if self.multiline && trailing_break {
+            self.buffer.lines.push(BufferLine::new(
+                "",
+                AttrsList::new(attrs),
+                Shaping::Advanced,
+            ));
         }
+        self.apply_align(font_system);
     }
 
-    pub fn set_text(&mut self, font_system: &mut FontSystem, text: &str) {
-        self.buffer.set_text(
-            font_system,
-            text,
-            Attrs::new().family(Family::SansSerif),
-            Shaping::Advanced,
-        );
+    // cosmic-text starts a new buffer line after every paragraph separator, not only after `\n`,
+    // and some of them are several bytes long. Returns whether the text ends with one.
+    fn set_line_starts(&mut self, text: &str) -> bool {
+        let origin = text.as_ptr() as usize;
+        let lines: Vec<&str> = BidiParagraphs::new(text).collect();
+        self.line_starts = lines
+            .iter()
+            .map(|line| line.as_ptr() as usize - origin)
+            .collect();
+
+        let text_end = lines
+            .last()
+            .map_or(0, |line| line.as_ptr() as usize - origin + line.len());
+        let trailing_break = text_end < text.len();
+        if self.line_starts.is_empty() {
+            self.line_starts.push(0);
+        } else if trailing_break && self.multiline {
+            self.line_starts.push(text.len());
+        }
+        trailing_break
+    }
+
+    // setting text resets the alignment of the buffer lines
+    fn apply_align(&mut self, font_system: &mut FontSystem) {
+        let align = self.style.cosmic_align();
+        self.buffer.lines.iter_mut().for_each(|line| {
+            line.set_align(align);
+        });
+        self.buffer.shape_until_scroll(font_system);
+    }
+
+    fn line_width(&self) -> f32 {
+        self.buffer
+            .layout_runs()
+            .fold(0.0, |width, run| run.line_w.max(width))
+    }
+
+    fn fits(&self) -> bool {
+        self.line_width() <= self.buffer.size().0
+    }
+
+    // finds the most graphemes that fit next to the ellipsis, shaping candidates as it goes
+    fn ellipsize(&mut self, font_system: &mut FontSystem, text: &str) -> String {
+        self.shape_text(font_system, text);
+        if self.fits() {
+            return text.to_string();
+        }
+
+        let graphemes: Vec<&str> = text.graphemes(true).collect();
+        let middle = self.style.overflow == TextOverflow::EllipsisMiddle;
+        let candidate = |kept: usize| {
+            if middle {
+                let (start, end) = (kept.div_ceil(2), kept / 2);
+                graphemes[..start].concat()
+                    + ELLIPSIS
+                    + &graphemes[graphemes.len() - end..].concat()
+            } else {
+                graphemes[..kept].concat() + ELLIPSIS
+            }
+        };
+
+        let (mut low, mut high) = (0, graphemes.len());
+        while low < high {
+            let kept = (low + high).div_ceil(2);
+            self.shape_text(font_system, &candidate(kept));
+            if self.fits() {
+                low = kept;
+            } else {
+                high = kept - 1;
+            }
+        }
+        candidate(low)
+    }
+
+    // only the layout depends on the font size, so shrinking does not reshape the text
+    fn shrink_to_fit(&mut self, font_system: &mut FontSystem) {
+        let TextOverflow::ShrinkToFit { min_font_size } = self.style.overflow else {
+            return;
+        };
+        if self.multiline {
+            return;
+        }
+
+        let TextStyle {
+            font_size,
+            line_height,
+            ..
+        } = self.style;
+        let mut size = font_size;
+        self.buffer.set_metrics(font_system, self.style.metrics());
+        while !self.fits() && size > min_font_size {
+            size = (size - SHRINK_STEP).max(min_font_size);
+            self.buffer.set_metrics(
+                font_system,
+                Metrics::new(size, line_height * size / font_size),
+            );
+        }
+    }
+
+    pub fn set_rich_text(&mut self, font_system: &mut FontSystem, spans: Vec<TextSpan>) {
+        let default_attrs = self.style.attrs();
+        // the span index is stored as metadata, so glyphs can be traced back to their span
+        let rich_text: Vec<(&str, Attrs)> = spans
+            .iter()
+            .enumerate()
+            .map(|(i, span)| {
+                let mut attrs = default_attrs.metadata(i);
+                if let Some(color) = span.color {
+                    attrs = attrs.color(color);
+                }
+                if let Some(weight) = span.weight {
+                    attrs = attrs.weight(weight);
+                }
+                if let Some(italic) = span.italic {
+                    attrs = attrs.style(if italic { Style::Italic } else { Style::Normal });
+                }
+                (span.text.as_str(), attrs)
+            })
+            .collect();
+        self.buffer
+            .set_rich_text(font_system, rich_text, Shaping::Advanced);
+        let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+        self.set_line_starts(&text);
+        self.apply_align(font_system);
+        self.shrink_to_fit(font_system);
+        self.spans = spans;
+    }
+
+    /// Returns the underline and strikethrough rectangles of the rich text spans.
+    pub fn decorations(&self) -> Vec<Rectangle> {
+        let font_size = self.buffer.metrics().font_size;
+        let thickness = (font_size * DECORATION_THICKNESS).max(1.0);
+        let (left, top) = (self.left(), self.top());
+        let mut decorations = Vec::new();
+        for run in self.buffer.layout_runs() {
+            for (i, span) in self.spans.iter().enumerate() {
+                let mut glyphs = run.glyphs.iter().filter(|glyph| glyph.metadata == i);
+                let Some(first) = glyphs.next() else {
+                    continue;
+                };
+                let (start, end) = glyphs
+                    .fold((first.x, first.x + first.w), |(start, end), glyph| {
+                        (start.min(glyph.x), end.max(glyph.x + glyph.w))
+                    });
+                let color = span.color.unwrap_or(self.color);
+                let color = [
+                    color.r() as f32 / 255.0,
+                    color.g() as f32 / 255.0,
+                    color.b() as f32 / 255.0,
+                ];
+                let offsets = [
+                    (span.underline, UNDERLINE_OFFSET),
+                    (span.strikethrough, STRIKETHROUGH_OFFSET),
+                ];
+                for (_, offset) in offsets.into_iter().filter(|(enabled, _)| *enabled) {
+                    let y = top + run.line_y + font_size * offset;
+                    decorations.push(Rectangle::new(
+                        self.clip(RectPos {
+                            top: y as u32,
+                            left: (left + start) as u32,
+                            bottom: (y + thickness) as u32,
+                            right: (left + end) as u32,
+                        }),
+                        color,
+                        color,
+                        color,
+                        color,
+                    ));
+                }
+            }
+        }
+        decorations
+    }
+
+    fn clip(&self, rect_pos: RectPos) -> RectPos {
+        RectPos {
+            top: rect_pos.top.clamp(self.rect_pos.top, self.rect_pos.bottom),
+            left: rect_pos.left.clamp(self.rect_pos.left, self.rect_pos.right),
+            bottom: rect_pos
+                .bottom
+                .clamp(self.rect_pos.top, self.rect_pos.bottom),
+            right: rect_pos
+                .right
+                .clamp(self.rect_pos.left, self.rect_pos.right),
+        }
+    }
+
+    pub fn rect_pos(&self) -> RectPos {
+        self.rect_pos
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.buffer.metrics().line_height
+    }
+
+    pub fn content_height(&self) -> f32 {
+        self.buffer.layout_runs().len() as f32 * self.line_height()
+    }
+
+    pub fn visible_height(&self) -> f32 {
+        (self.rect_pos.bottom - self.rect_pos.top) as f32
+    }
+
+    pub fn scroll_top(&self) -> f32 {
+        self.scroll_top
+    }
+
+    pub fn set_scroll_top(&mut self, scroll_top: f32) {
+        let max_scroll = (self.content_height() - self.visible_height()).max(0.0);
+        self.scroll_top = scroll_top.clamp(0.0, max_scroll);
+    }
+
+    pub fn scroll_left(&self) -> f32 {
+        self.scroll_left
+    }
+
+    pub fn visible_width(&self) -> f32 {
+        (self.rect_pos.right - self.rect_pos.left) as f32
     }
 
-    fn top(&self) -> f32 {
-        (self.rect_pos.bottom - (self.rect_pos.bottom - self.rect_pos.top) / 2) as f32
-            - (self.buffer.metrics().line_height / 2.0)
+    pub fn set_scroll_left(&mut self, scroll_left: f32) {
+        let (min_x, max_x) = self.horizontal_extent();
+        self.scroll_left = scroll_left.clamp(min_x, max_x - self.buffer.size().0);
+    }
+
+    /// Returns the horizontal range covered by the text, relative to the text origin, which is at
+    /// least the buffer width. Aligned text overflowing the buffer can start left of the origin.
+    pub fn horizontal_extent(&self) -> (f32, f32) {
+        self.buffer
+            .layout_runs()
+            .flat_map(|run| run.glyphs.iter())
+            .fold((0.0, self.buffer.size().0), |(min_x, max_x), glyph| {
+                (min_x.min(glyph.x), max_x.max(glyph.x + glyph.w))
+            })
+    }
+
+    /// Returns the x offset and line top of the caret at the given byte index, relative to the
+    /// text origin.
+    pub fn cursor_position(&self, index: usize) -> (f32, f32) {
+        let cursor = self.to_cursor(index);
+        let mut position = None;
+        for run in self
+            .buffer
+            .layout_runs()
+            .filter(|run| run.line_i == cursor.line)
+        {
+            // glyphs are in visual order, and right-to-left ones start at their right edge
+            for glyph in run.glyphs.iter() {
+                let rtl = glyph.level.is_rtl();
+                if glyph.start <= cursor.index && cursor.index < glyph.end {
+                    let x = if rtl { glyph.x + glyph.w } else { glyph.x };
+                    return (x, run.line_top);
+                }
+                if glyph.end == cursor.index {
+                    let x = if rtl { glyph.x } else { glyph.x + glyph.w };
+                    position = Some((x, run.line_top));
+                }
+            }
+            if run.glyphs.is_empty() {
+                position = Some((self.empty_line_x(), run.line_top));
+            }
+        }
+        position.unwrap_or((self.empty_line_x(), 0.0))
+    }
+
+    /// Returns whether the line containing the given byte index has a right-to-left base
+    /// direction.
+    pub fn is_rtl(&self, index: usize) -> bool {
+        let cursor = self.to_cursor(index);
+        self.buffer.lines[cursor.line]
+            .shape_opt()
+            .as_ref()
+            .is_some_and(|shape| shape.rtl)
+    }
+
+    /// Returns the `(x, line_top, width)` spans highlighting the given byte range, one per
+    /// visual line.
+    pub fn highlight(&self, start: usize, end: usize) -> Vec<(f32, f32, f32)> {
+        let start = self.to_cursor(start);
+        let end = self.to_cursor(end);
+        let mut spans = Vec::new();
+        for run in self.buffer.layout_runs() {
+            // mixed-direction text can select several disjoint spans on one visual line
+            let mut run_spans: Vec<(f32, f32)> = Vec::new();
+            for glyph in run.glyphs.iter().filter(|glyph| {
+                (run.line_i, glyph.start) >= (start.line, start.index)
+                    && (run.line_i, glyph.end) <= (end.line, end.index)
+            }) {
+                match run_spans.last_mut() {
+                    Some((x, width)) if (*x + *width - glyph.x).abs() < 0.5 => {
+                        *width += glyph.w;
+                    }
+                    _ => run_spans.push((glyph.x, glyph.w)),
+                }
+            }
+            spans.extend(
+                run_spans
+                    .into_iter()
+                    .map(|(x, width)| (x, run.line_top, width)),
+            );
+        }
+        spans
+    }
+
+    pub fn hit(&self, mouse_coords: PhysicalPosition<f64>) -> Option<usize> {
+        self.hit_layout(
+            mouse_coords.x as f32 - self.left(),
+            mouse_coords.y as f32 - self.top(),
+        )
+    }
+
+    /// Hit-tests a position relative to the text origin, returning the closest byte index.
+    pub fn hit_layout(&self, x: f32, y: f32) -> Option<usize> {
+        let Metrics {
+            font_size,
+            line_height,
+        } = self.buffer.metrics();
+
+        // clamp the hit into a line, so dragging above or below the text still selects
+        let line_y = self
+            .buffer
+            .layout_runs()
+            .find(|run| y < run.line_top + line_height)
+            .or_else(|| self.buffer.layout_runs().last())
+            .map(|run| run.line_y)?;
+
+        self.buffer
+            .hit(x, line_y - font_size / 2.0)
+            .map(|cursor| self.to_index(cursor))
+    }
+
+    // indices within a line break belong to the end of the line before it
+    fn to_cursor(&self, index: usize) -> Cursor {
+        let line_i = self
+            .line_starts
+            .iter()
+            .rposition(|line_start| *line_start <= index)
+            .unwrap_or(0)
+            .min(self.buffer.lines.len().saturating_sub(1));
+        let line_len = self
+            .buffer
+            .lines
+            .get(line_i)
+            .map_or(0, |line| line.text().len());
+        let line_start = self.line_starts.get(line_i).copied().unwrap_or(0);
+        Cursor::new(line_i, (index - line_start.min(index)).min(line_len))
+    }
+
+    fn to_index(&self, cursor: Cursor) -> usize {
+        self.line_starts.get(cursor.line).copied().unwrap_or(0) + cursor.index
+    }
+
+    pub fn left(&self) -> f32 {
+        self.rect_pos.left as f32 - self.scroll_left
+    }
+
+    pub fn top(&self) -> f32 {
+        let free_space = self.visible_height() - self.content_height();
+        let offset = match self.style.vertical_align {
+            VerticalAlign::Top => 0.0,
+            VerticalAlign::Middle => free_space / 2.0,
+            VerticalAlign::Bottom => free_space,
+        };
+
+        if self.multiline {
+            // overflowing multi-line text is scrolled instead
+            return self.rect_pos.top as f32 + offset.max(0.0) - self.scroll_top;
+        }
+
+        self.rect_pos.top as f32 + offset
+    }
+
+    // empty lines have no glyphs to place the caret at
+    fn empty_line_x(&self) -> f32 {
+        let width = self.buffer.size().0;
+        match (self.style.align, self.style.direction) {
+            (TextAlign::Center, _) => width / 2.0,
+            (TextAlign::Start | TextAlign::Justify, TextDirection::RightToLeft)
+            | (TextAlign::End, TextDirection::Auto | TextDirection::LeftToRight) => width,
+            _ => 0.0,
+        }
     }
 
     fn bounds(&self) -> TextBounds {
@@ -90,22 +651,10 @@ impl Text {
         }
     }
 
-    pub fn text_area(&self, is_active: bool) -> TextArea {
-        let text_width = self.get_text_width();
-        let TextWidth {
-            width,
-            buffer_width,
-        } = text_width;
-
-        let text_overlap = if width > buffer_width {
-            width - buffer_width
-        } else {
-            0.0
-        };
-
+    pub fn text_area(&self, is_active: bool) -> TextArea<'_> {
         TextArea {
             buffer: &self.buffer,
-            left: self.rect_pos.left as f32 - text_overlap,
+            left: self.left(),
             top: self.top(),
             scale: 1.0,
             bounds: self.bounds(),
@@ -0,0 +1,48 @@
+use crate::input_filter::InputFilter;
+use crate::rectangle::RectPos;
+use crate::text::TextStyle;
+use crate::text_field::{TextField, TextFieldConfig};
+use crate::validation::Validator;
+
+/// Like [`TextFieldConfig`], without the password options that make no sense for multi-line text.
+pub struct TextAreaConfig {
+    pub rect_pos: RectPos,
+    pub fill_color: [f32; 3],
+    pub fill_color_active: [f32; 3],
+    pub border_color: [f32; 3],
+    pub border_color_active: [f32; 3],
+    pub text_color: glyphon::Color,
+    pub text_style: TextStyle,
+    pub placeholder: Option<&'static str>,
+    pub max_length: Option<usize>,
+    pub input_filter: InputFilter,
+    pub validators: Vec<Validator>,
+}
+
+#[derive(Debug)]
+pub struct TextArea {
+    pub text_field: TextField,
+}
+
+impl TextArea {
+    pub fn new(cfg: TextAreaConfig, font_system: &mut glyphon::FontSystem) -> Self {
+        let cfg = TextFieldConfig {
+            rect_pos: cfg.rect_pos,
+            fill_color: cfg.fill_color,
+            fill_color_active: cfg.fill_color_active,
+            border_color: cfg.border_color,
+            border_color_active: cfg.border_color_active,
+            text_color: cfg.text_color,
+            text_style: cfg.text_style,
+            password: false,
+            password_toggle: false,
+            placeholder: cfg.placeholder,
+            max_length: cfg.max_length,
+            input_filter: cfg.input_filter,
+            validators: cfg.validators,
+        };
+        Self {
+            text_field: TextField::new_multiline(cfg, font_system),
+        }
+    }
+}
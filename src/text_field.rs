@@ -1,7 +1,17 @@
+use crate::clipboard::Clipboard;
+use crate::edit_history::{Edit, EditHistory, EditKind};
+use crate::input_filter::InputFilter;
 use crate::rectangle::{RectPos, Rectangle};
-use crate::text::Text;
-use glyphon::FontSystem;
+use crate::scroll::Scrollable;
+use crate::text::{Text, TextAlign, TextDirection, TextOverflow, TextStyle, VerticalAlign};
+use crate::validation::Validator;
+use glyphon::{Family, FontSystem, Weight};
+use std::borrow::Cow;
+use std::ops::Range;
 use std::time::SystemTime;
+use unicode_segmentation::UnicodeSegmentation;
+use winit::dpi::PhysicalPosition;
+use winit::window::CursorIcon;
 
 #[derive(Debug)]
 pub struct TextFieldConfig {
@@ -11,6 +21,19 @@ pub struct TextFieldConfig {
     pub border_color: [f32; 3],
     pub border_color_active: [f32; 3],
     pub text_color: glyphon::Color,
+    pub text_style: TextStyle,
+    pub password: bool,
+    pub password_toggle: bool,
+    pub placeholder: Option<&'static str>,
+    pub max_length: Option<usize>,
+    pub input_filter: InputFilter,
+    pub validators: Vec<Validator>,
+}
+
+#[derive(Debug)]
+pub struct Preedit {
+    pub text: String,
+    pub cursor: Option<(usize, usize)>,
 }
 
 #[derive(Debug)]
@@ -18,6 +41,21 @@ pub struct TextField {
     pub text: Text,
     pub rectangle: Rectangle,
     pub content: String,
+    pub cursor: usize,
+    pub selection_anchor: Option<usize>,
+    pub history: EditHistory,
+    pub preedit: Option<Preedit>,
+    pub multiline: bool,
+    pub password: bool,
+    pub password_revealed: bool,
+    pub password_toggle: Option<Rectangle>,
+    pub placeholder: Option<Text>,
+    pub max_length: Option<usize>,
+    pub input_filter: InputFilter,
+    pub validators: Vec<Validator>,
+    pub direction: TextDirection,
+    pub touched: bool,
+    pub error: Option<Text>,
     pub active: bool,
     pub last_cursor_blink: Option<SystemTime>,
 }
@@ -25,15 +63,152 @@ pub struct TextField {
 pub const CURSOR_BLINK_TIMEOUT_MS: u128 = 500;
 const PADDING: u32 = 10;
 const CURSOR_WIDTH: u32 = 2;
+const SELECTION_COLOR: [f32; 3] = [0.7, 0.8, 1.0];
+const PREEDIT_UNDERLINE_WIDTH: u32 = 2;
+const PASSWORD_BULLET: char = '•';
+const PLACEHOLDER_COLOR: glyphon::Color = glyphon::Color::rgb(150, 150, 150);
+pub const ERROR_COLOR: [f32; 3] = [0.8, 0.0, 0.0];
+const ERROR_TEXT_COLOR: glyphon::Color = glyphon::Color::rgb(200, 0, 0);
+const ERROR_TEXT_HEIGHT: u32 = 30;
+const ERROR_TEXT_STYLE: TextStyle = TextStyle {
+    font_size: 18.0,
+    line_height: 24.0,
+    family: Family::SansSerif,
+    weight: Weight::NORMAL,
+    italic: false,
+    align: TextAlign::Start,
+    vertical_align: VerticalAlign::Top,
+    overflow: TextOverflow::Ellipsis,
+    direction: TextDirection::Auto,
+};
+
+// the paragraph separators cosmic-text starts a new line at, and the Unicode line separator
+fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{1c}'..='\u{1e}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+// `\r\n` counts as a single break
+fn normalize_line_breaks(text: &str, separator: char) -> String {
+    let mut chars = text.chars().peekable();
+    let mut normalized = String::with_capacity(text.len());
+    while let Some(c) = chars.next() {
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            continue;
+        }
+        normalized.push(if is_line_break(c) { separator } else { c });
+    }
+    normalized
+}
+
+fn padded(cfg: &TextFieldConfig) -> RectPos {
+    let mut padded = RectPos {
+        top: cfg.rect_pos.top + PADDING,
+        left: cfg.rect_pos.left + PADDING,
+        right: cfg.rect_pos.right - PADDING,
+        bottom: cfg.rect_pos.bottom - PADDING,
+    };
+    match password_toggle_pos(cfg) {
+        Some(toggle_pos) if is_mirrored(cfg) => padded.left = toggle_pos.right + PADDING,
+        Some(toggle_pos) => padded.right = toggle_pos.left - PADDING,
+        None => (),
+    }
+    padded
+}
+
+// the reveal toggle is a square at the end of the field, which is its left edge in right-to-left
+// layouts
+fn password_toggle_pos(cfg: &TextFieldConfig) -> Option<RectPos> {
+    (cfg.password && cfg.password_toggle).then(|| {
+        let top = cfg.rect_pos.top + PADDING;
+        let bottom = cfg.rect_pos.bottom - PADDING;
+        let size = bottom - top;
+        let (left, right) = if is_mirrored(cfg) {
+            let left = cfg.rect_pos.left + PADDING;
+            (left, left + size)
+        } else {
+            let right = cfg.rect_pos.right - PADDING;
+            (right - size, right)
+        };
+        RectPos {
+            top,
+            left,
+            right,
+            bottom,
+        }
+    })
+}
+
+fn is_mirrored(cfg: &TextFieldConfig) -> bool {
+    cfg.text_style.direction == TextDirection::RightToLeft
+}
 
 impl TextField {
     pub fn new(cfg: TextFieldConfig, font_system: &mut glyphon::FontSystem) -> Self {
-        let padded_rect = RectPos {
-            top: cfg.rect_pos.top + PADDING,
-            left: cfg.rect_pos.left + PADDING,
-            right: cfg.rect_pos.right - PADDING,
-            bottom: cfg.rect_pos.bottom - PADDING,
-        };
+        // the content is scrolled instead, only the placeholder uses the overflow policy
+        let text = Text::new(
+            font_system,
+            padded(&cfg),
+            "",
+            cfg.text_color,
+            cfg.text_color,
+            TextStyle {
+                overflow: TextOverflow::Clip,
+                ..cfg.text_style
+            },
+        );
+        let placeholder = cfg.placeholder.map(|placeholder| {
+            Text::new(
+                font_system,
+                padded(&cfg),
+                placeholder,
+                PLACEHOLDER_COLOR,
+                PLACEHOLDER_COLOR,
+                cfg.text_style,
+            )
+        });
+        Self::with_text(cfg, text, placeholder, false)
+    }
+
+    pub fn new_multiline(cfg: TextFieldConfig, font_system: &mut glyphon::FontSystem) -> Self {
+        let text = Text::new_multiline(
+            font_system,
+            padded(&cfg),
+            "",
+            cfg.text_color,
+            cfg.text_color,
+            cfg.text_style,
+        );
+        let placeholder = cfg.placeholder.map(|placeholder| {
+            Text::new_multiline(
+                font_system,
+                padded(&cfg),
+                placeholder,
+                PLACEHOLDER_COLOR,
+                PLACEHOLDER_COLOR,
+                cfg.text_style,
+            )
+        });
+        Self::with_text(cfg, text, placeholder, true)
+    }
+
+    fn with_text(
+        cfg: TextFieldConfig,
+        text: Text,
+        placeholder: Option<Text>,
+        multiline: bool,
+    ) -> Self {
+        let password_toggle = password_toggle_pos(&cfg).map(|toggle_pos| {
+            Rectangle::new(
+                toggle_pos,
+                cfg.fill_color,
+                cfg.border_color_active,
+                cfg.border_color,
+                cfg.border_color_active,
+            )
+        });
         Self {
             rectangle: Rectangle::new(
                 cfg.rect_pos,
@@ -42,27 +217,47 @@ impl TextField {
                 cfg.border_color,
                 cfg.border_color_active,
             ),
-            text: Text::new(font_system, padded_rect, "", cfg.text_color, cfg.text_color),
+            text,
             content: String::new(),
+            cursor: 0,
+            selection_anchor: None,
+            history: EditHistory::default(),
+            preedit: None,
+            multiline,
+            password: cfg.password,
+            password_revealed: false,
+            password_toggle,
+            placeholder,
+            max_length: cfg.max_length,
+            input_filter: cfg.input_filter,
+            direction: cfg.text_style.direction,
+            validators: cfg.validators,
+            touched: false,
+            error: None,
             active: false,
             last_cursor_blink: None,
         }
     }
 
+    pub fn visible_text(&self) -> &Text {
+        match self.placeholder {
+            Some(ref placeholder) if self.content.is_empty() && self.preedit.is_none() => {
+                placeholder
+            }
+            _ => &self.text,
+        }
+    }
+
     pub fn get_cursor(&self) -> Rectangle {
-        let text_width = self.text.get_text_width();
-        let rect_pos = self.rectangle.position;
-        let left = if text_width.width > text_width.buffer_width {
-            rect_pos.right - PADDING
-        } else {
-            rect_pos.left + text_width.width as u32 + PADDING
-        };
+        let (x, line_top) = self.cursor_position(self.display_cursor());
+        let left = self.clamp_x(self.text.left() + x);
+        let (top, bottom) = self.line_span(line_top);
         Rectangle::new(
             RectPos {
-                top: rect_pos.top + PADDING,
+                top,
                 left,
                 right: left + CURSOR_WIDTH,
-                bottom: rect_pos.bottom - PADDING,
+                bottom,
             },
             [0.0, 0.0, 0.0],
             [0.0, 0.0, 0.0],
@@ -71,17 +266,541 @@ impl TextField {
         )
     }
 
+    pub fn get_preedit_underline(&self) -> Vec<Rectangle> {
+        let Some(ref preedit) = self.preedit else {
+            return Vec::new();
+        };
+        self.highlight(self.cursor, self.cursor + preedit.text.len())
+            .into_iter()
+            .map(|(x, line_top, width)| {
+                let (_, bottom) = self.line_span(line_top);
+                Rectangle::new(
+                    RectPos {
+                        top: bottom.saturating_sub(PREEDIT_UNDERLINE_WIDTH),
+                        left: self.clamp_x(self.text.left() + x),
+                        right: self.clamp_x(self.text.left() + x + width),
+                        bottom,
+                    },
+                    [0.0, 0.0, 0.0],
+                    [0.0, 0.0, 0.0],
+                    [0.0, 0.0, 0.0],
+                    [0.0, 0.0, 0.0],
+                )
+            })
+            .collect()
+    }
+
+    pub fn get_selection(&self) -> Vec<Rectangle> {
+        let Some(selection) = self.selection().filter(|_| self.preedit.is_none()) else {
+            return Vec::new();
+        };
+        self.highlight(selection.start, selection.end)
+            .into_iter()
+            .map(|(x, line_top, width)| {
+                let (top, bottom) = self.line_span(line_top);
+                Rectangle::new(
+                    RectPos {
+                        top,
+                        left: self.clamp_x(self.text.left() + x),
+                        right: self.clamp_x(self.text.left() + x + width),
+                        bottom,
+                    },
+                    SELECTION_COLOR,
+                    SELECTION_COLOR,
+                    SELECTION_COLOR,
+                    SELECTION_COLOR,
+                )
+            })
+            .filter(|rectangle| rectangle.position.top < rectangle.position.bottom)
+            .collect()
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.selection_anchor
+            .filter(|anchor| *anchor != self.cursor)
+            .map(|anchor| anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
     pub fn add_text(&mut self, font_system: &mut FontSystem, text: &str) {
+        self.insert(font_system, text, EditKind::Typing);
+    }
+
+    pub fn remove_character(&mut self, font_system: &mut FontSystem, by_word: bool) {
+        if let Some(selection) = self.selection() {
+            self.edit(font_system, selection, "", EditKind::Other);
+        } else if let Some(prev) = self.prev_boundary(by_word) {
+            self.edit(font_system, prev..self.cursor, "", EditKind::Backspace);
+        }
+    }
+
+    pub fn delete_character(&mut self, font_system: &mut FontSystem, by_word: bool) {
+        if let Some(selection) = self.selection() {
+            self.edit(font_system, selection, "", EditKind::Other);
+        } else if let Some(next) = self.next_boundary(by_word) {
+            self.edit(font_system, self.cursor..next, "", EditKind::Delete);
+        }
+    }
+
+    pub fn set_preedit(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        cursor: Option<(usize, usize)>,
+    ) {
+        if text.is_empty() {
+            self.cancel_preedit(font_system);
+        } else if self.active {
+            self.preedit = Some(Preedit {
+                text: text.to_owned(),
+                cursor,
+            });
+            self.update_text(font_system);
+        }
+    }
+
+    pub fn commit_preedit(&mut self, font_system: &mut FontSystem, text: &str) {
+        self.cancel_preedit(font_system);
+        if !text.is_empty() {
+            self.add_text(font_system, text);
+        }
+    }
+
+    pub fn cancel_preedit(&mut self, font_system: &mut FontSystem) {
+        if self.preedit.take().is_some() {
+            self.update_text(font_system);
+        }
+    }
+
+    pub fn undo(&mut self, font_system: &mut FontSystem) {
+        if self.active {
+            if let Some(edit) = self.history.undo() {
+                self.content
+                    .replace_range(edit.at..edit.at + edit.inserted.len(), &edit.deleted);
+                self.cursor = edit.cursor_before;
+                self.selection_anchor = edit.selection_anchor_before;
+                self.update_text(font_system);
+            }
+        }
+    }
+
+    pub fn redo(&mut self, font_system: &mut FontSystem) {
+        if self.active {
+            if let Some(edit) = self.history.redo() {
+                self.content
+                    .replace_range(edit.at..edit.at + edit.deleted.len(), &edit.inserted);
+                self.cursor = edit.at + edit.inserted.len();
+                self.selection_anchor = None;
+                self.update_text(font_system);
+            }
+        }
+    }
+
+    pub fn copy(&self, clipboard: &mut dyn Clipboard) {
+        if self.password {
+            return;
+        }
+        if let Some(selection) = self.selection() {
+            clipboard.set_text(&self.content[selection]);
+        }
+    }
+
+    pub fn cut(&mut self, font_system: &mut FontSystem, clipboard: &mut dyn Clipboard) {
+        if self.password {
+            return;
+        }
+        if let Some(selection) = self.selection() {
+            self.copy(clipboard);
+            self.edit(font_system, selection, "", EditKind::Other);
+        }
+    }
+
+    pub fn paste(&mut self, font_system: &mut FontSystem, clipboard: &mut dyn Clipboard) {
+        if let Some(text) = clipboard.get_text() {
+            // single-line fields turn pasted line breaks into spaces
+            let normalized = if self.multiline {
+                normalize_line_breaks(&text, '\n')
+            } else {
+                normalize_line_breaks(text.trim_end_matches(is_line_break), ' ')
+            };
+            self.insert(font_system, &normalized, EditKind::Other);
+        }
+    }
+
+    pub fn insert_newline(&mut self, font_system: &mut FontSystem) {
+        if self.multiline {
+            self.insert(font_system, "\n", EditKind::Other);
+        }
+    }
+
+    // arrow keys move visually, which is backwards in the text on right-to-left lines
+    pub fn move_cursor_left(&mut self, select: bool, by_word: bool) {
+        if self.is_rtl() {
+            self.move_cursor_forward(select, by_word);
+        } else {
+            self.move_cursor_backward(select, by_word);
+        }
+    }
+
+    pub fn move_cursor_right(&mut self, select: bool, by_word: bool) {
+        if self.is_rtl() {
+            self.move_cursor_backward(select, by_word);
+        } else {
+            self.move_cursor_forward(select, by_word);
+        }
+    }
+
+    fn move_cursor_backward(&mut self, select: bool, by_word: bool) {
+        match self.selection() {
+            Some(selection) if !select => self.set_cursor(selection.start, false),
+            _ => {
+                if let Some(prev) = self.prev_boundary(by_word) {
+                    self.set_cursor(prev, select);
+                }
+            }
+        }
+    }
+
+    fn move_cursor_forward(&mut self, select: bool, by_word: bool) {
+        match self.selection() {
+            Some(selection) if !select => self.set_cursor(selection.end, false),
+            _ => {
+                if let Some(next) = self.next_boundary(by_word) {
+                    self.set_cursor(next, select);
+                }
+            }
+        }
+    }
+
+    pub fn move_cursor_up(&mut self, select: bool) {
+        self.set_cursor(self.vertical_boundary(-1.0), select);
+    }
+
+    pub fn move_cursor_down(&mut self, select: bool) {
+        self.set_cursor(self.vertical_boundary(1.0), select);
+    }
+
+    pub fn move_cursor_to_start(&mut self, select: bool) {
+        let index = if self.multiline {
+            let (_, line_top) = self.cursor_position(self.cursor);
+            let x = if self.is_rtl() { f32::MAX } else { 0.0 };
+            self.hit_layout(x, line_top).unwrap_or(0)
+        } else {
+            0
+        };
+        self.set_cursor(index, select);
+    }
+
+    pub fn move_cursor_to_end(&mut self, select: bool) {
+        let index = if self.multiline {
+            let (_, line_top) = self.cursor_position(self.cursor);
+            let x = if self.is_rtl() { 0.0 } else { f32::MAX };
+            self.hit_layout(x, line_top).unwrap_or(self.content.len())
+        } else {
+            self.content.len()
+        };
+        self.set_cursor(index, select);
+    }
+
+    pub fn place_cursor(&mut self, mouse_coords: PhysicalPosition<f64>, select: bool) {
+        if let Some(index) = self.hit(mouse_coords) {
+            self.set_cursor(index, select);
+        }
+    }
+
+    pub fn select_word(&mut self, mouse_coords: PhysicalPosition<f64>) {
+        // masked text has no visible words, so select all of it
+        if self.is_masked() {
+            self.select_all();
+        } else if let Some(index) = self.hit(mouse_coords) {
+            if let Some(word) = self
+                .content
+                .split_word_bound_indices()
+                .map(|(start, word)| start..start + word.len())
+                .find(|word| index < word.end)
+                .or_else(|| {
+                    self.content
+                        .split_word_bound_indices()
+                        .next_back()
+                        .map(|(start, word)| start..start + word.len())
+                })
+            {
+                self.set_cursor(word.start, false);
+                self.set_cursor(word.end, true);
+            }
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.set_cursor(0, false);
+        self.set_cursor(self.content.len(), true);
+    }
+
+    fn set_cursor(&mut self, index: usize, select: bool) {
         if self.active {
-            self.content.push_str(text);
-            self.text.set_text(font_system, &self.content);
+            if select {
+                self.selection_anchor.get_or_insert(self.cursor);
+            } else {
+                self.selection_anchor = None;
+            }
+            self.cursor = index.min(self.content.len());
+            self.scroll_to_cursor();
+        }
+    }
+
+    pub fn toggle_password_visibility(&mut self, font_system: &mut FontSystem) {
+        if self.password {
+            self.password_revealed = !self.password_revealed;
+            self.update_text(font_system);
+        }
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.password && !self.password_revealed
+    }
+
+    fn update_text(&mut self, font_system: &mut FontSystem) {
+        let text = if self.is_masked() {
+            self.raw_text()
+                .graphemes(true)
+                .map(|_| PASSWORD_BULLET)
+                .collect()
+        } else {
+            self.raw_text().into_owned()
+        };
+        self.text.set_text(font_system, &text);
+        self.scroll_to_cursor();
+    }
+
+    // the content as displayed, including an IME preedit at the cursor
+    fn raw_text(&self) -> Cow<'_, str> {
+        match self.preedit {
+            Some(ref preedit) => {
+                let mut text = self.content.clone();
+                text.insert_str(self.cursor, &preedit.text);
+                Cow::Owned(text)
+            }
+            None => Cow::Borrowed(&self.content),
+        }
+    }
+
+    // masked text renders every grapheme as a bullet, so byte indices into the content and into
+    // the rendered text differ
+    fn display_index(&self, index: usize) -> usize {
+        if !self.is_masked() {
+            return index;
+        }
+        self.raw_text()[..index].graphemes(true).count() * PASSWORD_BULLET.len_utf8()
+    }
+
+    fn content_index(&self, index: usize) -> usize {
+        if !self.is_masked() {
+            return index;
+        }
+        let text = self.raw_text();
+        text.grapheme_indices(true)
+            .nth(index / PASSWORD_BULLET.len_utf8())
+            .map_or(text.len(), |(idx, _)| idx)
+    }
+
+    fn cursor_position(&self, index: usize) -> (f32, f32) {
+        self.text.cursor_position(self.display_index(index))
+    }
+
+    fn highlight(&self, start: usize, end: usize) -> Vec<(f32, f32, f32)> {
+        self.text
+            .highlight(self.display_index(start), self.display_index(end))
+    }
+
+    fn hit(&self, mouse_coords: PhysicalPosition<f64>) -> Option<usize> {
+        self.text
+            .hit(mouse_coords)
+            .map(|index| self.content_index(index))
+    }
+
+    fn is_rtl(&self) -> bool {
+        self.text.is_rtl(self.display_index(self.cursor))
+    }
+
+    fn hit_layout(&self, x: f32, y: f32) -> Option<usize> {
+        self.text
+            .hit_layout(x, y)
+            .map(|index| self.content_index(index))
+    }
+
+    fn insert(&mut self, font_system: &mut FontSystem, text: &str, kind: EditKind) {
+        let (range, kind) = match self.selection() {
+            Some(selection) => (selection, EditKind::Other),
+            None => (self.cursor..self.cursor, kind),
+        };
+
+        let filtered: String = text
+            .chars()
+            .filter(|c| (self.multiline && *c == '\n') || self.input_filter.accepts(*c))
+            .collect();
+
+        let text = match self.max_length {
+            Some(max_length) => {
+                let kept = self.content.graphemes(true).count()
+                    - self.content[range.clone()].graphemes(true).count();
+                let remaining = max_length.saturating_sub(kept);
+                filtered
+                    .grapheme_indices(true)
+                    .nth(remaining)
+                    .map_or(filtered.as_str(), |(idx, _)| &filtered[..idx])
+            }
+            None => filtered.as_str(),
+        };
+
+        if !text.is_empty() {
+            self.edit(font_system, range, text, kind);
         }
     }
 
-    pub fn remove_character(&mut self, font_system: &mut FontSystem) {
+    fn edit(
+        &mut self,
+        font_system: &mut FontSystem,
+        range: Range<usize>,
+        text: &str,
+        kind: EditKind,
+    ) {
         if self.active {
-            self.content.pop();
-            self.text.set_text(font_system, &self.content);
+            self.history.push(Edit {
+                at: range.start,
+                deleted: self.content[range.clone()].to_owned(),
+                inserted: text.to_owned(),
+                cursor_before: self.cursor,
+                selection_anchor_before: self.selection_anchor,
+                kind,
+                time: SystemTime::now(),
+            });
+            self.cursor = range.start + text.len();
+            self.selection_anchor = None;
+            self.content.replace_range(range, text);
+            self.update_text(font_system);
+        }
+    }
+
+    fn vertical_boundary(&self, lines: f32) -> usize {
+        let (x, line_top) = self.cursor_position(self.cursor);
+        let y = line_top + self.text.line_height() * (lines + 0.5);
+        if y < 0.0 {
+            0
+        } else if y > self.text.content_height() {
+            self.content.len()
+        } else {
+            self.hit_layout(x, y).unwrap_or(self.cursor)
+        }
+    }
+
+    fn prev_boundary(&self, by_word: bool) -> Option<usize> {
+        if self.cursor == 0 {
+            return None;
+        }
+        let before = &self.content[..self.cursor];
+        if by_word && self.is_masked() {
+            Some(0)
+        } else if by_word {
+            before
+                .unicode_word_indices()
+                .next_back()
+                .map(|(idx, _)| idx)
+                .or(Some(0))
+        } else {
+            before
+                .grapheme_indices(true)
+                .next_back()
+                .map(|(idx, _)| idx)
+        }
+    }
+
+    fn next_boundary(&self, by_word: bool) -> Option<usize> {
+        if self.cursor == self.content.len() {
+            return None;
+        }
+        let after = &self.content[self.cursor..];
+        if by_word && self.is_masked() {
+            Some(self.content.len())
+        } else if by_word {
+            after
+                .unicode_word_indices()
+                .next()
+                .map(|(idx, word)| self.cursor + idx + word.len())
+                .or(Some(self.content.len()))
+        } else {
+            after
+                .graphemes(true)
+                .next()
+                .map(|grapheme| self.cursor + grapheme.len())
+        }
+    }
+
+    fn display_cursor(&self) -> usize {
+        match self.preedit {
+            Some(ref preedit) => {
+                self.cursor
+                    + preedit
+                        .cursor
+                        .map_or(preedit.text.len(), |(start, _)| start)
+            }
+            None => self.cursor,
+        }
+    }
+
+    fn clamp_x(&self, x: f32) -> u32 {
+        let rect_pos = self.text.rect_pos();
+        x.clamp(rect_pos.left as f32, rect_pos.right as f32) as u32
+    }
+
+    // the visible part of a line, as the text might be aligned or scrolled within the field
+    fn line_span(&self, line_top: f32) -> (u32, u32) {
+        let rect_pos = self.text.rect_pos();
+        let min = rect_pos.top as f32;
+        let max = rect_pos.bottom as f32;
+        let top = self.text.top() + line_top;
+        (
+            top.clamp(min, max) as u32,
+            (top + self.text.line_height()).clamp(min, max) as u32,
+        )
+    }
+
+    // multi-line text scrolls vertically, single-line text horizontally to keep the caret in view
+    fn scroll_to_cursor(&mut self) {
+        let (x, line_top) = self.cursor_position(self.display_cursor());
+        if self.multiline {
+            let line_bottom = line_top + self.text.line_height();
+            let scroll_top = self.text.scroll_top();
+            let visible_height = self.text.visible_height();
+            if line_top < scroll_top {
+                self.text.set_scroll_top(line_top);
+            } else if line_bottom > scroll_top + visible_height {
+                self.text.set_scroll_top(line_bottom - visible_height);
+            } else {
+                // re-clamp, as the content might have shrunk
+                self.text.set_scroll_top(scroll_top);
+            }
+        } else {
+            let scroll_left = self.text.scroll_left();
+            let visible_width = self.text.visible_width();
+            if x < scroll_left {
+                self.text.set_scroll_left(x);
+            } else if x > scroll_left + visible_width {
+                self.text.set_scroll_left(x - visible_width);
+            } else {
+                self.text.set_scroll_left(scroll_left);
+            }
+        }
+    }
+
+    // the password toggle is a button inside the field
+    pub fn cursor_icon(&self, mouse_coords: PhysicalPosition<f64>) -> CursorIcon {
+        if self
+            .password_toggle
+            .as_ref()
+            .is_some_and(|toggle| toggle.is_hovered(mouse_coords))
+        {
+            CursorIcon::Pointer
+        } else {
+            CursorIcon::Text
         }
     }
 
@@ -92,8 +811,237 @@ impl TextField {
         }
     }
 
+    /// Returns the message of the first validator the content fails.
+    pub fn validation_error(&self) -> Option<&'static str> {
+        self.validators
+            .iter()
+            .find(|validator| !validator.rule.check(&self.content))
+            .map(|validator| validator.message)
+    }
+
+    /// Shows the given error message below the field, or hides it.
+    pub fn set_error(&mut self, font_system: &mut FontSystem, message: Option<&str>) {
+        self.error = message.map(|message| {
+            let RectPos {
+                left,
+                bottom,
+                right,
+                ..
+            } = self.rectangle.position;
+            Text::new(
+                font_system,
+                RectPos {
+                    top: bottom,
+                    left: left + PADDING,
+                    bottom: bottom + ERROR_TEXT_HEIGHT,
+                    right: right - PADDING,
+                },
+                message,
+                ERROR_TEXT_COLOR,
+                ERROR_TEXT_COLOR,
+                TextStyle {
+                    direction: self.direction,
+                    ..ERROR_TEXT_STYLE
+                },
+            )
+        });
+    }
+
     pub fn set_inactive(&mut self) {
+        // errors are only shown once the user left the field
+        if self.active {
+            self.touched = true;
+        }
         self.active = false;
+        self.selection_anchor = None;
         self.last_cursor_blink = None;
     }
 }
+
+// multi-line fields scroll vertically, single-line ones horizontally
+impl Scrollable for TextField {
+    fn scroll_by(&mut self, dx: f32, dy: f32) -> (f32, f32) {
+        let (scroll_left, scroll_top) = (self.text.scroll_left(), self.text.scroll_top());
+        if self.multiline {
+            self.text.set_scroll_top(scroll_top + dy);
+        } else {
+            self.text.set_scroll_left(scroll_left + dx);
+        }
+        (
+            self.text.scroll_left() - scroll_left,
+            self.text.scroll_top() - scroll_top,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::MemoryClipboard;
+    use crate::fonts::bundled_font_system;
+
+    fn config(input_filter: InputFilter) -> TextFieldConfig {
+        TextFieldConfig {
+            rect_pos: RectPos {
+                top: 0,
+                left: 0,
+                bottom: 200,
+                right: 300,
+            },
+            fill_color: [1.0, 1.0, 1.0],
+            fill_color_active: [1.0, 1.0, 1.0],
+            border_color: [0.0, 0.0, 0.0],
+            border_color_active: [0.0, 0.0, 0.0],
+            text_color: glyphon::Color::rgb(0, 0, 0),
+            text_style: TextStyle::default(),
+            password: false,
+            password_toggle: false,
+            placeholder: None,
+            max_length: None,
+            input_filter,
+            validators: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn edits_after_multi_byte_paragraph_separator() {
+        let mut font_system = bundled_font_system();
+        let mut text_field = TextField::new_multiline(config(InputFilter::None), &mut font_system);
+        text_field.set_active();
+        text_field.add_text(&mut font_system, "a\u{2029}b");
+
+        // click at the end of the second line
+        let text = text_field.visible_text();
+        let y = text.top() + text.line_height() * 1.5;
+        text_field.place_cursor(PhysicalPosition::new(290.0, y as f64), false);
+        assert_eq!(text_field.cursor, "a\u{2029}b".len());
+
+        text_field.remove_character(&mut font_system, false);
+        assert_eq!(text_field.content, "a\u{2029}");
+    }
+
+    fn active_field(
+        font_system: &mut FontSystem,
+        cfg: TextFieldConfig,
+        multiline: bool,
+        text: &str,
+    ) -> TextField {
+        let mut text_field = if multiline {
+            TextField::new_multiline(cfg, font_system)
+        } else {
+            TextField::new(cfg, font_system)
+        };
+        text_field.set_active();
+        text_field.add_text(font_system, text);
+        text_field
+    }
+
+    // selects the characters from `start` to the end of the text
+    fn select_from(text_field: &mut TextField, start: usize) {
+        text_field.move_cursor_to_start(false);
+        (0..start).for_each(|_| text_field.move_cursor_right(false, false));
+        text_field.move_cursor_to_end(true);
+    }
+
+    #[test]
+    fn copy_cut_and_paste_selection() {
+        let mut font_system = bundled_font_system();
+        let mut clipboard = MemoryClipboard::default();
+        let mut text_field = active_field(
+            &mut font_system,
+            config(InputFilter::None),
+            false,
+            "hello world",
+        );
+
+        text_field.copy(&mut clipboard);
+        assert_eq!(clipboard.get_text(), None);
+
+        select_from(&mut text_field, 6);
+        text_field.copy(&mut clipboard);
+        assert_eq!(clipboard.get_text().as_deref(), Some("world"));
+        assert_eq!(text_field.content, "hello world");
+
+        text_field.cut(&mut font_system, &mut clipboard);
+        assert_eq!(text_field.content, "hello ");
+        assert_eq!(text_field.cursor, 6);
+
+        text_field.move_cursor_to_start(false);
+        text_field.paste(&mut font_system, &mut clipboard);
+        assert_eq!(text_field.content, "worldhello ");
+        assert_eq!(text_field.cursor, 5);
+    }
+
+    #[test]
+    fn paste_replaces_selection() {
+        let mut font_system = bundled_font_system();
+        let mut clipboard = MemoryClipboard::default();
+        clipboard.set_text("there");
+        let mut text_field = active_field(
+            &mut font_system,
+            config(InputFilter::None),
+            false,
+            "hello world",
+        );
+
+        select_from(&mut text_field, 6);
+        text_field.paste(&mut font_system, &mut clipboard);
+        assert_eq!(text_field.content, "hello there");
+        assert_eq!(text_field.selection(), None);
+    }
+
+    #[test]
+    fn password_fields_do_not_copy_or_cut() {
+        let mut font_system = bundled_font_system();
+        let mut clipboard = MemoryClipboard::default();
+        clipboard.set_text("old");
+        let cfg = TextFieldConfig {
+            password: true,
+            ..config(InputFilter::None)
+        };
+        let mut text_field = active_field(&mut font_system, cfg, false, "secret");
+
+        text_field.select_all();
+        text_field.copy(&mut clipboard);
+        text_field.cut(&mut font_system, &mut clipboard);
+        assert_eq!(clipboard.get_text().as_deref(), Some("old"));
+        assert_eq!(text_field.content, "secret");
+
+        text_field.paste(&mut font_system, &mut clipboard);
+        assert_eq!(text_field.content, "old");
+    }
+
+    #[test]
+    fn paste_normalizes_line_breaks() {
+        let mut font_system = bundled_font_system();
+        let mut clipboard = MemoryClipboard::default();
+        clipboard.set_text("a\r\nb\rc\u{2028}d\u{2029}e\u{85}f\n");
+
+        let mut text_area = active_field(&mut font_system, config(InputFilter::None), true, "");
+        text_area.paste(&mut font_system, &mut clipboard);
+        assert_eq!(text_area.content, "a\nb\nc\nd\ne\nf\n");
+
+        let mut text_field = active_field(&mut font_system, config(InputFilter::None), false, "");
+        text_field.paste(&mut font_system, &mut clipboard);
+        assert_eq!(text_field.content, "a b c d e f");
+        assert_eq!(
+            text_field.visible_text().content_height(),
+            text_field.visible_text().line_height()
+        );
+    }
+
+    #[test]
+    fn paste_respects_input_filter_and_max_length() {
+        let mut font_system = bundled_font_system();
+        let mut clipboard = MemoryClipboard::default();
+        clipboard.set_text("12ab345");
+        let cfg = TextFieldConfig {
+            max_length: Some(4),
+            ..config(InputFilter::Digits)
+        };
+        let mut text_field = active_field(&mut font_system, cfg, false, "");
+
+        text_field.paste(&mut font_system, &mut clipboard);
+        assert_eq!(text_field.content, "1234");
+    }
+}
@@ -0,0 +1,102 @@
+use crate::text::{TextStyle, MULTILINE_BUFFER_HEIGHT};
+use glyphon::{Buffer, Family, FontSystem, Shaping, Weight, Wrap};
+use std::collections::HashMap;
+
+const MAX_CACHE_ENTRIES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextMeasurement {
+    pub width: f32,
+    pub height: f32,
+    /// Distance from the top of the text to the baseline of its first line.
+    pub baseline: f32,
+}
+
+// floats are keyed by their bits, as only exact repeats of a measurement are cached
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct MeasureKey {
+    text: String,
+    font_size: u32,
+    line_height: u32,
+    family: Family<'static>,
+    weight: Weight,
+    italic: bool,
+    max_width: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+pub struct TextMeasurer {
+    cache: HashMap<MeasureKey, TextMeasurement>,
+}
+
+impl TextMeasurer {
+    /// Measures how big `text` would be laid out with `style`, wrapping at word boundaries when
+    /// it is wider than `max_width`.
+    pub fn measure(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        style: &TextStyle,
+        max_width: Option<f32>,
+    ) -> TextMeasurement {
+        let key = MeasureKey {
+            text: text.to_string(),
+            font_size: style.font_size.to_bits(),
+            line_height: style.line_height.to_bits(),
+            family: style.family,
+            weight: style.weight,
+            italic: style.italic,
+            max_width: max_width.map(f32::to_bits),
+        };
+        if let Some(measurement) = self.cache.get(&key) {
+            return *measurement;
+        }
+
+        let measurement = layout(font_system, text, style, max_width);
+        if self.cache.len() == MAX_CACHE_ENTRIES {
+            self.cache.clear();
+        }
+        self.cache.insert(key, measurement);
+        measurement
+    }
+}
+
+fn layout(
+    font_system: &mut FontSystem,
+    text: &str,
+    style: &TextStyle,
+    max_width: Option<f32>,
+) -> TextMeasurement {
+    let mut buffer = Buffer::new(font_system, style.metrics());
+    buffer.set_size(
+        font_system,
+        max_width.unwrap_or(f32::MAX),
+        MULTILINE_BUFFER_HEIGHT,
+    );
+    buffer.set_wrap(
+        font_system,
+        if max_width.is_some() {
+            Wrap::Word
+        } else {
+            Wrap::None
+        },
+    );
+    buffer.set_text(font_system, text, style.attrs(), Shaping::Advanced);
+
+    let (width, lines, baseline) =
+        buffer
+            .layout_runs()
+            .fold((0.0, 0, None), |(width, lines, baseline), run| {
+                (
+                    run.line_w.max(width),
+                    lines + 1,
+                    baseline.or(Some(run.line_y)),
+                )
+            });
+
+    TextMeasurement {
+        width,
+        height: lines as f32 * style.line_height,
+        baseline: baseline.unwrap_or(style.line_height),
+    }
+}
@@ -0,0 +1,77 @@
+use crate::input_filter::InputFilter;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub enum Rule {
+    Required,
+    MinLength(usize),
+    MaxLength(usize),
+    Format(InputFilter),
+    Custom(Box<dyn Fn(&str) -> bool>),
+}
+
+pub struct Validator {
+    pub rule: Rule,
+    pub message: &'static str,
+}
+
+pub struct FormValues<'a> {
+    values: Vec<(usize, &'a str)>,
+}
+
+pub struct CrossFieldValidator {
+    pub field: usize,
+    pub message: &'static str,
+    pub check: Box<dyn Fn(&FormValues) -> bool>,
+}
+
+pub struct Form {
+    pub fields: Vec<usize>,
+    pub submit_button: usize,
+    /// Whether Enter in one of the form's single-line fields presses the submit button.
+    pub submit_on_enter: bool,
+    pub cross_field_validators: Vec<CrossFieldValidator>,
+}
+
+impl Rule {
+    // apart from `Required`, rules only apply to non-empty values
+    pub fn check(&self, value: &str) -> bool {
+        match self {
+            Rule::Required => !value.trim().is_empty(),
+            _ if value.is_empty() => true,
+            Rule::MinLength(min_length) => value.graphemes(true).count() >= *min_length,
+            Rule::MaxLength(max_length) => value.graphemes(true).count() <= *max_length,
+            Rule::Format(filter) => value.chars().all(|c| filter.accepts(c)),
+            Rule::Custom(check) => check(value),
+        }
+    }
+}
+
+impl std::fmt::Debug for Validator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Validator")
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl<'a> FormValues<'a> {
+    pub fn new(values: Vec<(usize, &'a str)>) -> Self {
+        Self { values }
+    }
+
+    pub fn get(&self, field: usize) -> &str {
+        self.values
+            .iter()
+            .find(|(id, _)| *id == field)
+            .map_or("", |(_, value)| value)
+    }
+}
+
+impl Form {
+    pub fn cross_field_error(&self, field: usize, values: &FormValues) -> Option<&'static str> {
+        self.cross_field_validators
+            .iter()
+            .find(|validator| validator.field == field && !(validator.check)(values))
+            .map(|validator| validator.message)
+    }
+}